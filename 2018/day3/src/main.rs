@@ -1,9 +1,26 @@
 use std::collections::{HashMap, HashSet};
 
+use common::{pattern, ParseError};
+
 const PUZZLE: &str = include_str!("input.txt");
 
+/// A claim as `(id, left, top, width, height)`.
+type Claim = (i32, i32, i32, i32, i32);
+
+fn parse_claims(input: &str) -> Result<Vec<Claim>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            pattern!("#{id} @ {left},{top}: {width}x{height}")
+                .parse::<Claim>(line)
+                .map_err(|err| err.at_line(idx))
+        })
+        .collect()
+}
+
 fn main() {
-    let claims = PUZZLE.lines().collect::<Vec<&str>>();
+    let claims = parse_claims(PUZZLE).unwrap();
 
     let mut cuts: HashMap<(i32, i32), i32> = HashMap::new();
 
@@ -12,26 +29,20 @@ fn main() {
     let mut overlap_cuts = HashSet::new();
     let mut all_cuts = HashSet::new();
 
-    claims.iter().for_each(|claim| {
-        // Format: [0:ID, 1:fromTop, 2:fromLeft, 3:width, 4:height]
-        let params = claim
-            .split(|c| c == '#' || c == ' ' || c == '@' || c == ':' || c == 'x' || c == ',')
-            .flat_map(|e| e.parse::<i32>().ok())
-            .collect::<Vec<i32>>();
-
-        for x in params[1]..params[1]+params[3] {
-            for y in params[2]..params[2]+params[4] {
+    claims.iter().for_each(|&(id, left, top, width, height)| {
+        for x in left..left + width {
+            for y in top..top + height {
                 *cuts.entry((x, y)).or_insert(0) += 1;
 
                 // Insert all cuts on a hash set and register all overlapped claims into
                 // another set to perform a diff between the two at the end
-                all_cuts.insert(params[0]);
+                all_cuts.insert(id);
 
                 if overlap_ids.contains_key(&(x, y)) {
                     overlap_cuts.insert(overlap_ids[&(x, y)]);
-                    overlap_cuts.insert(params[0]);
+                    overlap_cuts.insert(id);
                 } else {
-                    overlap_ids.insert((x, y), params[0]);
+                    overlap_ids.insert((x, y), id);
                 }
             }
         }
@@ -43,3 +54,12 @@ fn main() {
     let non_overlapped: HashSet<_> = all_cuts.difference(&overlap_cuts).collect();
     println!("Non Overlap: {:?}", non_overlapped);
 }
+
+#[test]
+fn fuzz_claims() -> common::Result<()> {
+    let corpus = ["#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n"];
+    let claims = |input: &str| parse_claims(input).map(|claims| claims.len());
+
+    common::fuzz_text(&corpus, 20_000, &[claims])?;
+    Ok(())
+}
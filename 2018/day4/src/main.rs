@@ -1,19 +1,74 @@
 extern crate chrono;
 
 use chrono::{NaiveDateTime, Timelike};
+use common::{Diagnostic, Pattern, Result};
 use std::collections::HashMap;
 
 const DATA: &str = include_str!("./input.txt");
 
-fn main() {
-    let mut records = Vec::new();
+/// Length of the timestamp at the start of each log entry, like `[1518-11-05 00:55]`
+const TIMESTAMP_LEN: usize = 18;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Record {
+    BeginsShift(u32),
+    FallsAsleep,
+    WakesUp,
+}
+
+/// Parse a log entry, `index` is the line where the entry is located.
+fn parse_entry(
+    index: usize,
+    entry: &str,
+    guard_pattern: &Pattern,
+) -> Result<(NaiveDateTime, Record), Diagnostic> {
     // [1518-11-05 00:55] wakes up
-    DATA.lines().for_each(|entry| {
-        let (date, rec_type) = entry.split_at(18);
-        let date = NaiveDateTime::parse_from_str(date, "[%Y-%m-%d %H:%M]").unwrap();
-        records.push((date, rec_type.trim()));
-    });
+    let timestamp_error = || {
+        Diagnostic::on_line(
+            index,
+            entry,
+            0..TIMESTAMP_LEN,
+            "a timestamp like `[1518-11-05 00:55]`",
+        )
+    };
+
+    if !entry.is_char_boundary(TIMESTAMP_LEN) {
+        return Err(timestamp_error());
+    }
+
+    let (date, rec_type) = entry.split_at(TIMESTAMP_LEN);
+    let date = NaiveDateTime::parse_from_str(date, "[%Y-%m-%d %H:%M]")
+        .map_err(|_| timestamp_error())?;
+
+    let record = match rec_type.trim() {
+        "falls asleep" => Record::FallsAsleep,
+        "wakes up" => Record::WakesUp,
+        other => match guard_pattern.parse::<(u32,)>(other) {
+            Ok((guard,)) => Record::BeginsShift(guard),
+            Err(_) => {
+                let start = entry.len() - rec_type.trim_start().len();
+                return Err(Diagnostic::on_line(
+                    index,
+                    entry,
+                    start..entry.trim_end().len(),
+                    "`falls asleep`, `wakes up` or `Guard #<id> begins shift`",
+                ));
+            }
+        },
+    };
+
+    Ok((date, record))
+}
+
+fn main() -> Result<()> {
+    let guard_pattern = Pattern::new("Guard #{id} begins shift")?;
+    let mut records = DATA
+        .lines()
+        .enumerate()
+        .map(|(index, entry)| {
+            parse_entry(index, entry, &guard_pattern).map_err(|err| err.in_file("input.txt"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Sort the logs by cronological order
     records.sort_by(|prev, cur| prev.0.cmp(&cur.0));
@@ -24,18 +79,16 @@ fn main() {
     let mut guard_sleep_freq: HashMap<u32, HashMap<u32, u32>> = HashMap::new();
 
     for (date, record) in records {
-        // Record the guard that begin the shift
-        if record.ends_with("begins shift") {
-            current_guard = str::parse(&record.split(" ").nth(1).unwrap()[1..]).unwrap();
-            guard_asleep = None;
-            continue;
-        }
-
         match record {
-            "falls asleep" => {
+            // Record the guard that begin the shift
+            Record::BeginsShift(guard) => {
+                current_guard = guard;
+                guard_asleep = None;
+            }
+            Record::FallsAsleep => {
                 guard_asleep = Some(date);
             }
-            "wakes up" => {
+            Record::WakesUp => {
                 // Compute the number of minutes that the guard was sleeping and
                 // sum it to the hash map.
                 let asleep_time = guard_asleep.expect("Invalid event");
@@ -51,7 +104,6 @@ fn main() {
                         .or_default() += 1;
                 }
             }
-            other => panic!("Invalid log {:?}", other)
         }
     }
 
@@ -88,4 +140,38 @@ fn main() {
         .unwrap();
 
     println!("Part 2: {}", guard * minute);
+
+    Ok(())
+}
+
+#[test]
+fn fuzz_entries() -> Result<()> {
+    let corpus = ["[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up
+"];
+    let guard_pattern = Pattern::new("Guard #{id} begins shift")?;
+
+    common::fuzz(&corpus, 20_000, |data| {
+        for (index, entry) in String::from_utf8_lossy(data).lines().enumerate() {
+            if let Err(err) = parse_entry(index, entry, &guard_pattern) {
+                let _ = err.in_file("input.txt").to_string();
+            }
+        }
+    })?;
+    Ok(())
 }
@@ -0,0 +1,25 @@
+use common::Rng;
+
+/// A polymer of `size` units where most of them react away, often in long chains, as every
+/// unit is likely to be followed by the opposite of the last one still standing.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut standing: Vec<u8> = Vec::new();
+    let mut polymer = Vec::with_capacity(size);
+
+    for _ in 0..size {
+        let unit = match standing.last() {
+            Some(&last) if rng.chance(0.45) => last ^ 0x20,
+            _ => *rng.choose(b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+        };
+
+        // a random unit can react too, `standing` is what `reduce` would have left
+        if standing.last() == Some(&(unit ^ 0x20)) {
+            standing.pop();
+        } else {
+            standing.push(unit);
+        }
+        polymer.push(unit);
+    }
+
+    String::from_utf8(polymer).unwrap()
+}
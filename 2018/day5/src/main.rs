@@ -1,3 +1,7 @@
+use common::generate_request;
+
+mod generator;
+
 const DATA: &str = include_str!("./input.txt");
 const REACT_DISTANCE: u8 = 32;
 
@@ -33,7 +37,7 @@ fn reduce(polymer: &str) -> String {
 fn improve_polymer(polymer: &str) -> usize {
     let mut shorter = polymer.len();
 
-    for c in b'a'..b'z' {
+    for c in b'a'..=b'z' {
         let char_to_remove = c as char;
         let upper_char = char_to_remove.to_ascii_uppercase();
         let new_polymer = polymer.replace(char_to_remove, "").replace(upper_char, "");
@@ -48,6 +52,66 @@ fn improve_polymer(polymer: &str) -> usize {
 }
 
 fn main() {
+    // `--generate <seed> <size>` prints a random polymer instead
+    if let Some((mut rng, size)) = generate_request(std::env::args().skip(1)).unwrap() {
+        print!("{}", generator::generate(&mut rng, size));
+        return;
+    }
+
     println!("Part 1: {}", reduce(DATA).len());
     println!("Part 2: {}", improve_polymer(DATA));
-}
\ No newline at end of file
+}
+
+/// Removes the first two units that react, again and again until there are none left.
+#[cfg(test)]
+fn brute_force_reduce(polymer: &str) -> String {
+    let mut units = polymer.as_bytes().to_vec();
+
+    while let Some(index) = units
+        .windows(2)
+        .position(|pair| pair[0] != pair[1] && pair[0].eq_ignore_ascii_case(&pair[1]))
+    {
+        units.drain(index..index + 2);
+    }
+
+    String::from_utf8(units).unwrap()
+}
+
+/// Tries removing every type of unit, from `a` to `z`.
+#[cfg(test)]
+fn brute_force_improve(polymer: &str) -> usize {
+    (b'a'..=b'z')
+        .map(|removed| {
+            let kept: String = polymer
+                .chars()
+                .filter(|unit| !unit.eq_ignore_ascii_case(&(removed as char)))
+                .collect();
+            brute_force_reduce(&kept).len()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn test_example() {
+    assert_eq!(reduce("dabAcCaCBAcCcaDA"), "dabCBAcaDA");
+    assert_eq!(improve_polymer("dabAcCaCBAcCcaDA"), 4);
+    assert_eq!(improve_polymer("zabZ"), 2);
+}
+
+#[test]
+fn test_differential() -> common::Result<()> {
+    common::differential(
+        200,
+        generator::generate,
+        |polymer| {
+            common::shrink_vec(polymer.as_bytes())
+                .into_iter()
+                .map(|units| String::from_utf8(units).unwrap())
+                .collect()
+        },
+        |polymer| (reduce(polymer), improve_polymer(polymer)),
+        |polymer| (brute_force_reduce(polymer), brute_force_improve(polymer)),
+    )?;
+    Ok(())
+}
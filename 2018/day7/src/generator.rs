@@ -0,0 +1,34 @@
+use std::collections::BTreeSet;
+
+use common::Rng;
+
+/// Instructions for `size` steps, at most 26 as they are letters. The steps are put in a
+/// random order and each one depends on at least one of the steps before it, plus some others,
+/// so there is always a way to finish all of them.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut steps = (b'A'..=b'Z').map(char::from).collect::<Vec<_>>();
+    rng.shuffle(&mut steps);
+    steps.truncate(size.clamp(2, 26));
+
+    let mut dependencies = BTreeSet::new();
+    for after in 1..steps.len() {
+        dependencies.insert((rng.index(after), after));
+        for before in 0..after {
+            if rng.chance(0.3) {
+                dependencies.insert((before, after));
+            }
+        }
+    }
+
+    let mut lines = dependencies
+        .into_iter()
+        .map(|(before, after)| {
+            format!(
+                "Step {} must be finished before step {} can begin.\n",
+                steps[before], steps[after]
+            )
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut lines);
+    lines.concat()
+}
@@ -1,5 +1,3 @@
-#![feature(drain_filter)]
-
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
@@ -8,12 +6,15 @@ use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::error::Error;
 
+use common::generate_request;
 use regex::Regex;
 
+mod generator;
+
 const DATA: &str = include_str!("./input.txt");
 
 macro_rules! err {
-    ($($tt:tt)*) => { Err(Box::<Error>::from(format!($($tt)*))) }
+    ($($tt:tt)*) => { Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
 /// Just to make the code more semantic.
@@ -32,7 +33,7 @@ impl Dependency {}
 
 /// Implement parsers for a Dependency
 impl FromStr for Dependency {
-    type Err = Box<Error>;
+    type Err = Box<dyn Error>;
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
         lazy_static! {
@@ -73,7 +74,8 @@ fn find_next_step(requirements: &Required, done: &HashSet<Step>, taken: &HashSet
     possible_steps
 }
 
-fn part1(requirements: &Required) {
+/// Order of the steps when the first available one in alphabetical order is always done next.
+fn order(requirements: &Required) -> String {
     let mut order: Vec<Step> = Vec::new();
     let mut done: HashSet<Step> = HashSet::new();
 
@@ -89,8 +91,11 @@ fn part1(requirements: &Required) {
         order.push(next_step);
     }
 
-    let result: String = order.iter().collect();
-    println!("Part1: {}", result);
+    order.iter().collect()
+}
+
+fn part1(requirements: &Required) {
+    println!("Part1: {}", order(requirements));
 }
 
 /// Enum that represents the status of a worker
@@ -106,7 +111,7 @@ enum Status {
     },
 }
 
-fn apply_tick(workers: &mut Vec<Status>) -> (Vec<Step>) {
+fn apply_tick(workers: &mut Vec<Status>) -> Vec<Step> {
     let mut finished_steps: Vec<Step> = Vec::new();
 
     for index in 0..workers.len() {
@@ -188,15 +193,14 @@ fn part2(requirements: &Required) {
     println!("Part2: {}", seconds);
 }
 
-fn main() {
+/// Build map with all dependencies for each step.
+fn parse(data: &str) -> Result<Required, Box<dyn Error>> {
     // Build the list of dependencies
-    let dependencies = DATA
+    let dependencies = data
         .lines()
         .map(|line| line.parse::<Dependency>())
-        .collect::<Result<Vec<Dependency>, _>>()
-        .unwrap();
+        .collect::<Result<Vec<Dependency>, _>>()?;
 
-    // Build map with all dependencies for each step.
     let mut requirements: Required = HashMap::new();
     dependencies
         .iter()
@@ -205,6 +209,52 @@ fn main() {
             requirements.entry(dep.required).or_default();
         });
 
+    Ok(requirements)
+}
+
+fn main() {
+    // `--generate <seed> <size>` prints random instructions for that many steps instead
+    if let Some((mut rng, size)) = generate_request(std::env::args().skip(1)).unwrap() {
+        print!("{}", generator::generate(&mut rng, size));
+        return;
+    }
+
+    let requirements = parse(DATA).unwrap();
+
     part1(&requirements);
     part2(&requirements);
 }
+
+#[test]
+fn solve_generated_instructions() -> common::Result<()> {
+    let input = common::generate_reproducible(generator::generate, 7, 20)?;
+    let requirements = parse(&input).map_err(|err| common::format_err!("{}", err))?;
+    assert_eq!(requirements.len(), 20);
+
+    // every step is done once, after all the ones it requires
+    let order = order(&requirements);
+    assert_eq!(order.len(), 20);
+    for (position, step) in order.chars().enumerate() {
+        assert!(requirements[&step]
+            .iter()
+            .all(|required| order[..position].contains(*required)));
+    }
+    Ok(())
+}
+
+#[test]
+fn fuzz_dependencies() -> common::Result<()> {
+    let corpus = ["Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.
+"];
+
+    let steps = |input: &str| parse(input).map(|requirements| order(&requirements));
+
+    common::fuzz_text(&corpus, 20_000, &[steps])?;
+    Ok(())
+}
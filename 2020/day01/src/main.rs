@@ -1,27 +1,32 @@
-use std::iter::FromIterator;
 use std::{collections::HashSet, fs};
 
 const TARGET: u32 = 2020;
 
-/// makes use of a HashSet to get an entry that possibly matches the criteria. When found it multiplies to get the
-/// result.
-fn find_two(expenses: &Vec<u32>) -> Option<u32> {
-    let entries = HashSet::<u32>::from_iter(expenses.iter().copied());
+/// makes use of a HashSet to get an entry that matches the criteria among the ones seen before, so an entry is
+/// never paired with itself.
+fn find_pair(expenses: &[u32], target: u32) -> Option<(u32, u32)> {
+    let mut seen = HashSet::new();
 
-    entries
-        .iter()
-        .find_map(|x| entries.get(&(TARGET - x)).map(|x| x * x))
+    expenses.iter().find_map(|&x| {
+        let pair = target
+            .checked_sub(x)
+            .filter(|y| seen.contains(y))
+            .map(|y| (x, y));
+        seen.insert(x);
+        pair
+    })
 }
 
-/// uses the same approach as the first, but with an additional variable.
-fn find_three(expenses: &Vec<u32>) -> Option<u32> {
-    let entries = HashSet::<u32>::from_iter(expenses.iter().copied());
+/// looks for the two entries that add up to the target. When found it multiplies them to get the result.
+fn find_two(expenses: &[u32]) -> Option<u32> {
+    find_pair(expenses, TARGET).map(|(x, y)| x * y)
+}
 
-    entries.iter().find_map(|x| {
-        entries
-            .iter()
-            .filter(|&y| y != x)
-            .find_map(|y| entries.get(&(TARGET - x - y)).map(|z| x * y * z))
+/// uses the same approach as the first, looking for the pair among the entries after each one.
+fn find_three(expenses: &[u32]) -> Option<u32> {
+    expenses.iter().enumerate().find_map(|(idx, &x)| {
+        let (y, z) = find_pair(&expenses[idx + 1..], TARGET.checked_sub(x)?)?;
+        Some(x * y * z)
     })
 }
 
@@ -37,3 +42,69 @@ fn main() {
     println!("Part 1: {}", find_two(&all_expenses).unwrap());
     println!("Part 2: {}", find_three(&all_expenses).unwrap());
 }
+
+/// Products of every way to pick `count` different entries that add up to `target`.
+#[cfg(test)]
+fn brute_force(expenses: &[u32], count: usize, target: u32) -> Vec<u32> {
+    if count == 0 {
+        return if target == 0 { vec![1] } else { vec![] };
+    }
+
+    let mut products = Vec::new();
+    for (idx, &x) in expenses.iter().enumerate() {
+        if let Some(rest) = target.checked_sub(x) {
+            for product in brute_force(&expenses[idx + 1..], count - 1, rest) {
+                products.push(x * product);
+            }
+        }
+    }
+    products
+}
+
+#[test]
+fn test_example() {
+    let expenses = [1721, 979, 366, 299, 675, 1456];
+
+    assert_eq!(find_two(&expenses), Some(514579));
+    assert_eq!(find_three(&expenses), Some(241861950));
+    assert_eq!(find_two(&[1010, 3]), None);
+    assert_eq!(find_two(&[1010, 3, 1010]), Some(1020100));
+}
+
+#[test]
+fn test_differential() -> common::Result<()> {
+    // at most one pair and one triple add up to the target, so there's a single right answer
+    let generate = |rng: &mut common::Rng, size: usize| {
+        let pair = rng.range(0..TARGET as u64 + 1) as u32;
+        let first = rng.range(0..TARGET as u64 / 2) as u32;
+        let second = rng.range(0..(TARGET - first) as u64 + 1) as u32;
+        let planted = [pair, TARGET - pair, first, second, TARGET - first - second];
+
+        let mut expenses = Vec::new();
+        let random = (0..size / 5).map(|_| rng.range(0..TARGET as u64 + 1) as u32);
+        for expense in planted.into_iter().chain(random) {
+            expenses.push(expense);
+            if brute_force(&expenses, 2, TARGET).len() > 1
+                || brute_force(&expenses, 3, TARGET).len() > 1
+            {
+                expenses.pop();
+            }
+        }
+        rng.shuffle(&mut expenses);
+        expenses
+    };
+
+    common::differential(
+        100,
+        generate,
+        |expenses| common::shrink_vec(expenses),
+        |expenses| (find_two(expenses), find_three(expenses)),
+        |expenses| {
+            (
+                brute_force(expenses, 2, TARGET).first().copied(),
+                brute_force(expenses, 3, TARGET).first().copied(),
+            )
+        },
+    )?;
+    Ok(())
+}
@@ -1,11 +1,6 @@
-#[macro_use]
-extern crate lazy_static;
-extern crate regex;
-
-use std::fs;
 use std::str::FromStr;
 
-use regex::Regex;
+use common::{pattern, LineSource, OkIterator, ParseError, Result};
 
 struct Entry {
     min: usize,
@@ -15,21 +10,17 @@ struct Entry {
 }
 
 impl FromStr for Entry {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // 1-4 m: mrfmmbjxr
-        lazy_static! {
-            static ref ENTRY_REGEXP: Regex = Regex::new(r"(\d+)-(\d+) (\w): (\w+)$").unwrap();
-        }
-
-        let groups = ENTRY_REGEXP.captures(s).unwrap();
+        let captures = pattern!("{min}-{max} {char}: {password}").captures(s)?;
 
         Ok(Self {
-            min: groups.get(1).unwrap().as_str().parse().unwrap(),
-            max: groups.get(2).unwrap().as_str().parse().unwrap(),
-            char: groups.get(3).unwrap().as_str().parse().unwrap(),
-            password: groups.get(4).unwrap().as_str().parse().unwrap(),
+            min: captures.name("min")?,
+            max: captures.name("max")?,
+            char: captures.name::<char>("char")?.to_string(),
+            password: captures.name("password")?,
         })
     }
 }
@@ -43,42 +34,67 @@ fn is_valid_password(entry: &Entry) -> bool {
     number_of_chars >= entry.min && number_of_chars <= entry.max
 }
 
-fn number_of_valid_password(passwords: &Vec<Entry>) -> usize {
-    passwords
-        .into_iter()
-        .filter(|e| is_valid_password(e))
-        .collect::<Vec<&Entry>>()
-        .len()
+fn is_valid_password_2(entry: &Entry) -> bool {
+    // positions start at 1, the ones outside of the password never have the letter
+    let has_char = |position: usize| {
+        position
+            .checked_sub(1)
+            .and_then(|idx| entry.password.chars().nth(idx))
+            .map_or(false, |c| entry.char.starts_with(c))
+    };
+
+    has_char(entry.min) != has_char(entry.max)
 }
 
-fn is_valid_password_2(entry: &Entry) -> bool {
-    let chars = vec![
-        entry.password.chars().nth(entry.min - 1).unwrap(),
-        entry.password.chars().nth(entry.max - 1).unwrap(),
-    ];
-
-    chars[0] != chars[1]
-        && (chars[0] == entry.char.chars().next().unwrap()
-            || chars[1] == entry.char.chars().next().unwrap())
+fn main() -> Result<()> {
+    // one entry at a time, both policies are checked at once
+    let (valid, valid_2) = LineSource::open("input.txt")?
+        .skip_blank()
+        .parse::<Entry>()
+        .ok_fold((0, 0), |(valid, valid_2), entry| {
+            (
+                valid + is_valid_password(&entry) as usize,
+                valid_2 + is_valid_password_2(&entry) as usize,
+            )
+        })?;
+
+    println!("Part 1: {}", valid);
+    println!("Part 2: {}", valid_2);
+
+    Ok(())
 }
 
-fn number_of_valid_password_2(passwords: &Vec<Entry>) -> usize {
-    passwords
-        .into_iter()
-        .filter(|e| is_valid_password_2(e))
-        .collect::<Vec<&Entry>>()
-        .len()
+#[test]
+fn test_example() -> Result<()> {
+    let entries = ["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"]
+        .iter()
+        .map(|line| line.parse::<Entry>())
+        .ok_collect_vec()?;
+
+    let valid = entries.iter().map(is_valid_password).collect::<Vec<_>>();
+    let valid_2 = entries.iter().map(is_valid_password_2).collect::<Vec<_>>();
+    assert_eq!(valid, [true, false, true]);
+    assert_eq!(valid_2, [true, false, false]);
+
+    // out of the password, so only the first position has the letter
+    assert!(is_valid_password_2(&"1-9 a: abc".parse()?));
+    assert!(!is_valid_password_2(&"0-9 a: abc".parse()?));
+    Ok(())
 }
 
-fn main() {
-    let file_content = fs::read_to_string("input.txt").expect("File doesn't exists");
+#[test]
+fn fuzz_entries() -> Result<()> {
+    let corpus = ["1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n"];
 
-    let passwords: Vec<Entry> = file_content
-        .trim()
-        .lines()
-        .map(|s| s.parse().unwrap())
-        .collect();
+    let valid = |input: &str| {
+        LineSource::new(input.as_bytes())
+            .skip_blank()
+            .parse::<Entry>()
+            .ok_fold(0, |valid, entry| {
+                valid + is_valid_password(&entry) as usize + is_valid_password_2(&entry) as usize
+            })
+    };
 
-    println!("Part 1: {}", number_of_valid_password(&passwords));
-    println!("Part 2: {}", number_of_valid_password_2(&passwords));
+    common::fuzz_text(&corpus, 20_000, &[valid])?;
+    Ok(())
 }
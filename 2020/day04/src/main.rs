@@ -1,5 +1,7 @@
 use std::{fs, str::FromStr};
 
+use common::{blocks, Diagnose, ParseError, Result};
+
 #[derive(Debug, Default)]
 struct Document {
     // Birth Year
@@ -31,45 +33,111 @@ impl Document {
 }
 
 impl FromStr for Document {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut document = Document::default();
 
-        s.split(" ")
-            .map(|e| {
-                let mut parts = e.split(":");
-                (parts.next().unwrap(), parts.next().unwrap())
-            })
-            .for_each(|(key, value)| {
-                match key {
-                    "byr" => document.byr = Some(String::from_str(value).unwrap()),
-                    "iyr" => document.iyr = Some(String::from_str(value).unwrap()),
-                    "eyr" => document.eyr = Some(String::from_str(value).unwrap()),
-                    "hgt" => document.hgt = Some(String::from_str(value).unwrap()),
-                    "hcl" => document.hcl = Some(String::from_str(value).unwrap()),
-                    "ecl" => document.ecl = Some(String::from_str(value).unwrap()),
-                    "pid" => document.pid = Some(String::from_str(value).unwrap()),
-                    "cid" => document.cid = Some(String::from_str(value).unwrap()),
-                    _ => panic!("invalid property"),
-                };
-            });
+        let mut offset = 0;
+        for property in s.split(|c: char| c.is_ascii_whitespace()) {
+            let start = offset;
+            offset += property.len() + 1;
+
+            if property.is_empty() {
+                continue;
+            }
+
+            let (key, value) = property.split_once(':').ok_or_else(|| {
+                ParseError::new(start..start + property.len(), "a `key:value` property")
+            })?;
+            let value = Some(value.to_string());
+
+            match key {
+                "byr" => document.byr = value,
+                "iyr" => document.iyr = value,
+                "eyr" => document.eyr = value,
+                "hgt" => document.hgt = value,
+                "hcl" => document.hcl = value,
+                "ecl" => document.ecl = value,
+                "pid" => document.pid = value,
+                "cid" => document.cid = value,
+                _ => {
+                    return Err(ParseError::new(
+                        start..start + key.len(),
+                        "a known property",
+                    ))
+                }
+            };
+        }
 
         Ok(document)
     }
 }
 
-fn main() {
+fn main() -> Result<()> {
     let file_content = fs::read_to_string("input.txt").expect("File doesn't exists");
 
-    let documents: Vec<Document> = file_content
-        .trim()
-        // split by empty lines
-        .split("\n\n")
-        // make all documents have just one line and convert it into a Document
-        .map(|e| e.replace("\n", " ").parse::<Document>().unwrap())
-        .collect();
+    // documents are separated by empty lines
+    let documents: Vec<Document> = blocks(&file_content)
+        .map(|document| {
+            document
+                .text()
+                .parse::<Document>()
+                .in_source(&file_content, document.offset())
+                .map_err(|err| err.in_file("input.txt"))
+        })
+        .collect::<Result<_, _>>()?;
 
     let len_valid_docs = documents.iter().filter(|&doc| doc.is_valid()).count();
     println!("Part 1: {:?}", len_valid_docs);
+
+    Ok(())
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+";
+
+#[test]
+fn test_example() -> Result<()> {
+    let documents = blocks(EXAMPLE)
+        .map(|document| document.text().parse::<Document>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let valid = documents.iter().map(Document::is_valid).collect::<Vec<_>>();
+    assert_eq!(valid, [true, false, true, false]);
+    Ok(())
+}
+
+#[test]
+fn fuzz_documents() -> Result<()> {
+    common::fuzz(&[EXAMPLE], 20_000, |data| {
+        let input = String::from_utf8_lossy(data);
+        for document in blocks(&input) {
+            match document.text().parse::<Document>() {
+                Ok(document) => {
+                    document.is_valid();
+                }
+                Err(err) => {
+                    let _ = Err::<(), _>(err)
+                        .in_source(&input, document.offset())
+                        .unwrap_err()
+                        .to_string();
+                }
+            }
+        }
+    })?;
+    Ok(())
 }
@@ -4,6 +4,7 @@ extern crate regex;
 
 use std::{fs, str::FromStr};
 
+use common::{blocks, Diagnose, ParseError, Result};
 use regex::Regex;
 use units::HeightUnit;
 
@@ -75,75 +76,126 @@ impl Document {
 }
 
 impl FromStr for Document {
-    type Err = ();
+    type Err = ParseError;
 
+    /// Values that don't follow their rules are left out, so the document isn't valid, but a
+    /// property that isn't `key:value` or that we don't know about is an error.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref HAIR_REGEX: Regex = Regex::new(r"^#[0-9a-f]{6}$").unwrap();
+            static ref PID_REGEX: Regex = Regex::new(r"^[0-9]{9}$").unwrap();
+        };
+        let valid_eye_colors = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
         let mut document = Document::default();
 
-        s.split(" ")
-            .map(|e| {
-                let mut parts = e.split(":");
-                (parts.next().unwrap(), parts.next().unwrap())
-            })
-            .for_each(|(key, value)| {
-                match key {
-                    "byr" => document.byr = Some(String::from_str(value).unwrap().parse().unwrap()),
-                    "iyr" => document.iyr = Some(String::from_str(value).unwrap().parse().unwrap()),
-                    "eyr" => document.eyr = Some(String::from_str(value).unwrap().parse().unwrap()),
-                    "hgt" => document.hgt = HeightUnit::from_str(value).ok(),
-                    "hcl" => {
-                        lazy_static! {
-                            static ref HAIR_REGEX: Regex = Regex::new(r"^#[0-9a-f]{6}$").unwrap();
-                        };
-
-                        document.hcl = if HAIR_REGEX.is_match(value) {
-                            Some(String::from_str(value).unwrap())
-                        } else {
-                            None
-                        }
-                    }
-                    "ecl" => {
-                        let valid_values = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
-                        document.ecl = if valid_values.contains(&value) {
-                            Some(String::from_str(value).unwrap())
-                        } else {
-                            None
-                        }
-                    }
-                    "pid" => {
-                        lazy_static! {
-                            static ref PID_REGEX: Regex = Regex::new(r"^[0-9]{9}$").unwrap();
-                        };
-
-                        document.pid = if PID_REGEX.is_match(value) {
-                            Some(String::from_str(value).unwrap())
-                        } else {
-                            None
-                        }
-                    }
-                    "cid" => document.cid = Some(String::from_str(value).unwrap()),
-                    _ => panic!("invalid property"),
-                };
-            });
+        let mut offset = 0;
+        for property in s.split(|c: char| c.is_ascii_whitespace()) {
+            let start = offset;
+            offset += property.len() + 1;
+
+            if property.is_empty() {
+                continue;
+            }
+
+            let (key, value) = property.split_once(':').ok_or_else(|| {
+                ParseError::new(start..start + property.len(), "a `key:value` property")
+            })?;
+
+            match key {
+                "byr" => document.byr = value.parse().ok(),
+                "iyr" => document.iyr = value.parse().ok(),
+                "eyr" => document.eyr = value.parse().ok(),
+                "hgt" => document.hgt = HeightUnit::from_str(value).ok(),
+                "hcl" => document.hcl = HAIR_REGEX.is_match(value).then(|| value.to_string()),
+                "ecl" => {
+                    document.ecl = valid_eye_colors.contains(&value).then(|| value.to_string())
+                }
+                "pid" => document.pid = PID_REGEX.is_match(value).then(|| value.to_string()),
+                "cid" => document.cid = Some(value.to_string()),
+                _ => {
+                    return Err(ParseError::new(
+                        start..start + key.len(),
+                        "a known property",
+                    ))
+                }
+            };
+        }
 
         Ok(document)
     }
 }
 
-fn main() {
+fn main() -> Result<()> {
     let file_content = fs::read_to_string("input.txt").expect("File doesn't exists");
 
-    let documents: Vec<Document> = file_content
-        .trim()
-        // split by empty lines
-        .split("\n\n")
-        // make all documents have just one line and convert it into a Document
-        .map(|e| e.replace("\n", " ").parse::<Document>().unwrap())
-        .collect();
+    // documents are separated by empty lines
+    let documents: Vec<Document> = blocks(&file_content)
+        .map(|document| {
+            document
+                .text()
+                .parse::<Document>()
+                .in_source(&file_content, document.offset())
+                .map_err(|err| err.in_file("input.txt"))
+        })
+        .collect::<Result<_, _>>()?;
 
     let len_valid_docs_pt2 = documents
         .iter()
         .filter(|&doc| doc.has_valid_values())
         .count();
     println!("Part 2: {:?}", len_valid_docs_pt2);
+
+    Ok(())
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533077 byr:1946
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+";
+
+#[test]
+fn test_example() -> Result<()> {
+    let documents = blocks(EXAMPLE)
+        .map(|document| document.text().parse::<Document>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let valid = documents
+        .iter()
+        .map(Document::has_valid_values)
+        .collect::<Vec<_>>();
+    assert_eq!(valid, [false, false, true, true]);
+    assert!("byr:1937 foo:1".parse::<Document>().is_err());
+    Ok(())
+}
+
+#[test]
+fn fuzz_documents() -> Result<()> {
+    common::fuzz(&[EXAMPLE], 20_000, |data| {
+        let input = String::from_utf8_lossy(data);
+        for document in blocks(&input) {
+            match document.text().parse::<Document>() {
+                Ok(document) => {
+                    document.has_valid_values();
+                }
+                Err(err) => {
+                    let _ = Err::<(), _>(err)
+                        .in_source(&input, document.offset())
+                        .unwrap_err()
+                        .to_string();
+                }
+            }
+        }
+    })?;
+    Ok(())
 }
@@ -1,83 +1,177 @@
-use itertools::Itertools;
+use std::io::BufRead;
 
-use std::fs;
+use common::*;
 
-fn compute_part1(raw_data: String) -> u32 {
-    let all_numbers_per_row: Vec<Vec<String>> = raw_data
-        .lines()
-        .map(|line| {
-            line.chars()
-                .filter_map(|ch| ch.is_numeric().then(|| ch.to_string()))
-                .collect()
-        })
-        .collect();
+/// Words that can be read as digits, found all at once with a single pass over each line.
+struct Vocabulary {
+    matcher: Matcher,
+    values: Vec<u32>,
+}
 
-    all_numbers_per_row
-        .iter()
-        .map(|v| {
-            let number = format!("{}{}", v.first().unwrap(), v.last().unwrap());
-            number.parse::<u32>().unwrap()
+impl Vocabulary {
+    fn new<'a>(words: impl IntoIterator<Item = (&'a str, u32)>) -> Result<Self> {
+        let (words, values): (Vec<_>, Vec<_>) = words.into_iter().unzip();
+
+        Ok(Self {
+            matcher: Matcher::new(words)?,
+            values,
         })
-        .sum()
+    }
+
+    /// Only `1` to `9`.
+    fn numerals() -> Self {
+        Self::with_words(&[])
+    }
+
+    /// The numerals and the given words, the first word being one, the second two...
+    fn with_words(words: &[&str]) -> Self {
+        Self::with_digits(Self::NUMERALS.into_iter().zip(1..), words)
+    }
+
+    /// Like [`Vocabulary::with_words`], but `0` is read as a digit too.
+    fn with_zero(words: &[&str]) -> Self {
+        Self::with_digits(
+            [("0", 0)]
+                .into_iter()
+                .chain(Self::NUMERALS.into_iter().zip(1..)),
+            words,
+        )
+    }
+
+    const NUMERALS: [&'static str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+    fn with_digits<'a>(numerals: impl Iterator<Item = (&'a str, u32)>, words: &[&'a str]) -> Self {
+        Self::new(numerals.chain(words.iter().copied().zip(1..))).expect("the words are not empty")
+    }
+
+    fn english() -> Self {
+        Self::with_words(&[
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ])
+    }
+
+    /// The first digit and the last one of the line, which may share letters like `eightwo`.
+    /// When two words start at the same place the longest one is taken.
+    fn calibration_value(&self, line: &str) -> Option<u32> {
+        let mut found = self.matcher.find_overlapping(line);
+
+        let first = found.next()?;
+        let (first, last) = found.fold((first, first), |(first, last), found| {
+            (
+                if found.start <= first.start {
+                    found
+                } else {
+                    first
+                },
+                if found.start >= last.start {
+                    found
+                } else {
+                    last
+                },
+            )
+        });
+
+        Some(self.values[first.pattern] * 10 + self.values[last.pattern])
+    }
 }
 
-fn compute_part2(raw_data: String) -> u32 {
-    let valid_digits = [
-        "1", "2", "3", "4", "5", "6", "7", "8", "9", "one", "two", "three", "four", "five", "six",
-        "seven", "eight", "nine",
-    ];
+/// Add up the calibration values of all the lines, one line at a time.
+fn calibrate(lines: LineSource<impl BufRead>, vocabulary: &Vocabulary) -> Result<u64> {
+    lines.fold_lines(0, |sum, line| {
+        let value = vocabulary
+            .calibration_value(line)
+            .context("no digit found")?;
+        Ok(sum + value as u64)
+    })
+}
 
-    raw_data
-        .lines()
-        .map(|line| {
-            let (_, first) = valid_digits
-                .iter()
-                .enumerate()
-                .filter_map(|(idx, digit)| line.find(digit).map(|pos| (pos, idx)))
-                .min()
-                .expect("no first digit found");
-
-            let (_, last) = valid_digits
-                .iter()
-                .enumerate()
-                .filter_map(|(idx, digit)| line.rfind(digit).map(|pos| (pos, idx)))
-                .max()
-                .expect("no last digit found");
-
-            Ok::<(u32, u32), ()>((first as u32 % 9 + 1, last as u32 % 9 + 1))
-        })
-        .process_results(|it| it.map(|(d1, d2)| d1 * 10 + d2).sum::<u32>())
-        .unwrap()
+#[cfg(test)]
+fn compute_part1(raw_data: &str) -> Result<u64> {
+    calibrate(
+        LineSource::new(raw_data.as_bytes()),
+        &Vocabulary::numerals(),
+    )
+}
+
+#[cfg(test)]
+fn compute_part2(raw_data: &str) -> Result<u64> {
+    calibrate(LineSource::new(raw_data.as_bytes()), &Vocabulary::english())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day01/input.txt").expect("input file does not exists");
+fn main() -> Result<()> {
+    const INPUT: &str = "2023/day01/input.txt";
 
-    println!("Result part 1: {}", compute_part1(raw_data.clone()));
-    println!("Result part 2: {}", compute_part2(raw_data));
+    // the input is read as it goes, so it can be as big as needed
+    let part1 = calibrate(LineSource::open(INPUT)?, &Vocabulary::numerals())?;
+    println!("Result part 1: {}", part1);
+    let part2 = calibrate(LineSource::open(INPUT)?, &Vocabulary::english())?;
+    println!("Result part 2: {}", part2);
+
+    // other words for the digits can be given, from one to nine, like `un deux trois...`,
+    // and `--zero` first reads `0` as a digit too
+    let mut words = std::env::args().skip(1).peekable();
+    let zero = words.next_if(|word| word == "--zero").is_some();
+    let words = words.collect::<Vec<_>>();
+    if zero || !words.is_empty() {
+        let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+        let vocabulary = match zero {
+            true => Vocabulary::with_zero(&words),
+            false => Vocabulary::with_words(&words),
+        };
+        println!(
+            "Result with other words: {}",
+            calibrate(LineSource::open(INPUT)?, &vocabulary)?
+        );
+    }
+
+    Ok(())
+}
+
+common::examples! {
+    calibration: include_str!("../examples/1.txt") => {
+        compute_part1: 142,
+    }
+    spelled_out: include_str!("../examples/2.txt") => {
+        compute_part2: 281,
+    }
 }
 
 #[test]
-fn test_part1() -> Result<(), ()> {
-    const INPUT: &str = "1abc2
-pqr3stu8vwx
-a1b2c3d4e5f
-treb7uchet";
+fn test_other_vocabularies() -> Result<()> {
+    let french = Vocabulary::with_words(&[
+        "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+    ]);
+    assert_eq!(french.calibration_value("xdeuxsepthuitz"), Some(28));
+    assert_eq!(french.calibration_value("neuf"), Some(99));
+
+    let with_zero = Vocabulary::new([("zero", 0), ("one", 1), ("oner", 7), ("2", 2)])?;
+    assert_eq!(with_zero.calibration_value("zerone"), Some(1));
+    assert_eq!(with_zero.calibration_value("onerzero2"), Some(72));
+    assert_eq!(with_zero.calibration_value("ab"), None);
+
+    assert_eq!(Vocabulary::english().calibration_value("eightwo"), Some(82));
+    assert_eq!(Vocabulary::numerals().calibration_value("0a5b"), Some(55));
+    assert_eq!(Vocabulary::numerals().calibration_value("0"), None);
+    assert_eq!(
+        Vocabulary::with_zero(&[]).calibration_value("5a0b"),
+        Some(50)
+    );
+    assert_eq!(
+        Vocabulary::with_zero(&["one"]).calibration_value("0one"),
+        Some(1)
+    );
+    assert!(calibrate(LineSource::new(&b"1a\nb"[..]), &Vocabulary::numerals()).is_err());
 
-    assert_eq!(compute_part1(INPUT.to_string()), 142);
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<(), ()> {
-    const INPUT: &str = "two1nine
-eightwothree
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen";
-
-    assert_eq!(compute_part2(INPUT.to_string()), 281);
+fn fuzz_calibration() -> Result<()> {
+    let corpus = [
+        include_str!("../examples/1.txt"),
+        include_str!("../examples/2.txt"),
+    ];
+
+    common::fuzz_text(&corpus, 20_000, &[compute_part1, compute_part2])?;
     Ok(())
 }
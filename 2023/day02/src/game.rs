@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use common::{pattern, ParseError};
+
+/// Amount of cubes of each colour, either in a bag or in a handful drawn from it.
+pub type Cubes = BTreeMap<String, u32>;
+
+/// A draw that needs more cubes of a colour than the bag has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// index of the draw on the game
+    pub draw: usize,
+    pub color: String,
+    pub drawn: u32,
+    pub available: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    /// the handfuls of cubes, in the order they were shown
+    pub draws: Vec<Cubes>,
+}
+
+impl Game {
+    /// Every time a draw needs more cubes of a colour than there are in the bag.
+    pub fn violations(&self, bag: &Cubes) -> Vec<Violation> {
+        self.draws
+            .iter()
+            .enumerate()
+            .flat_map(|(draw, cubes)| {
+                cubes.iter().filter_map(move |(color, &drawn)| {
+                    let available = bag.get(color).copied().unwrap_or(0);
+                    (drawn > available).then(|| Violation {
+                        draw,
+                        color: color.clone(),
+                        drawn,
+                        available,
+                    })
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_possible(&self, bag: &Cubes) -> bool {
+        self.violations(bag).is_empty()
+    }
+
+    /// The smallest bag that could have been used, with the most cubes of each colour seen on
+    /// a single draw.
+    pub fn minimum_bag(&self) -> Cubes {
+        let mut bag = Cubes::new();
+        for (color, &count) in self.draws.iter().flatten() {
+            let most = bag.entry(color.clone()).or_default();
+            *most = (*most).max(count);
+        }
+        bag
+    }
+
+    /// Chances of seeing exactly these draws from the bag. Each draw takes as many cubes as it
+    /// shows at random and puts them back afterwards, so every draw follows a multivariate
+    /// hypergeometric distribution.
+    pub fn probability(&self, bag: &Cubes) -> f64 {
+        let total = bag.values().sum::<u32>();
+
+        self.draws
+            .iter()
+            .map(|cubes| {
+                if cubes.keys().any(|color| !bag.contains_key(color)) {
+                    return 0.0;
+                }
+
+                let drawn = cubes.values().sum::<u32>();
+                let ways = bag
+                    .iter()
+                    .map(|(color, &available)| {
+                        ln_binomial(available, cubes.get(color).copied().unwrap_or(0))
+                    })
+                    .sum::<Option<f64>>();
+
+                match (ways, ln_binomial(total, drawn)) {
+                    (Some(ways), Some(all)) => (ways - all).exp(),
+                    _ => 0.0,
+                }
+            })
+            .product()
+    }
+}
+
+/// `ln(C(n, k))`, `None` when there are not enough to choose from.
+fn ln_binomial(n: u32, k: u32) -> Option<f64> {
+    let k = k.min(n.checked_sub(k)?);
+    Some(
+        (0..k)
+            .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+            .sum(),
+    )
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        let captures = pattern!("Game {id}: {draws}").captures(s)?;
+        let id = captures.name::<u32>("id")?;
+        let all_draws = captures.named_str("draws").unwrap_or_default();
+
+        // every cube is followed by a single `,` or `;`, which keeps track of where it is
+        let mut offset = s.len() - all_draws.len();
+        let mut draws = Vec::new();
+        for draw in all_draws.split(';') {
+            let mut drawn = Cubes::new();
+
+            for cube in draw.split(',') {
+                let trimmed = cube.trim_start();
+                let start = offset + cube.len() - trimmed.len();
+                offset += cube.len() + 1;
+
+                let (count, color) = pattern!("{count} {color}")
+                    .parse::<(u32, String)>(trimmed)
+                    .map_err(|err| err.offset(start))?;
+                if drawn.insert(color, count).is_some() {
+                    return Err(ParseError::new(
+                        start..start + trimmed.len(),
+                        "a colour that wasn't on the draw yet",
+                    ));
+                }
+            }
+
+            draws.push(drawn);
+        }
+
+        Ok(Self { id, draws })
+    }
+}
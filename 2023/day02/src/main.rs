@@ -1,107 +1,171 @@
+mod game;
+
+use common::{LineSource, OkIterator, Result};
+use game::{Cubes, Game};
 use itertools::Itertools;
-use regex::Regex;
 
-use std::{fs, str::FromStr};
+use std::io::BufRead;
 
-struct Game {
-    id: u32,
-    red: u32,
-    green: u32,
-    blue: u32,
+fn bag(cubes: &[(&str, u32)]) -> Cubes {
+    cubes
+        .iter()
+        .map(|&(color, count)| (color.to_string(), count))
+        .collect()
 }
 
-impl Game {
-    pub fn is_possible(&self, red: u32, green: u32, blue: u32) -> bool {
-        self.red <= red && self.green <= green && self.blue <= blue
-    }
+/// Add up the ids of the games that could be played with 12 red, 13 green and 14 blue cubes.
+fn sum_possible_ids(lines: LineSource<impl BufRead>) -> Result<u64> {
+    let bag = bag(&[("red", 12), ("green", 13), ("blue", 14)]);
+
+    lines
+        .parse::<Game>()
+        .ok_fold(0, |sum, game| match game.is_possible(&bag) {
+            true => sum + game.id as u64,
+            false => sum,
+        })
 }
 
-impl FromStr for Game {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-        let re = Regex::new(
-            r"(Game (?P<game_number>[0-9]+)|(?P<color_count>[0-9]+) (?P<color>green|blue|red))",
-        )
-        .unwrap();
-
-        let mut captures = re.captures_iter(s);
-        let game_number = (captures.next().unwrap()["game_number"])
-            .parse::<u32>()
-            .unwrap();
-
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-
-        for cap in captures {
-            red = (&cap["color"] == "red")
-                .then(|| cap["color_count"].parse::<u32>().unwrap())
-                .map_or(red, |v| v.max(red));
-
-            blue = (&cap["color"] == "blue")
-                .then(|| cap["color_count"].parse::<u32>().unwrap())
-                .map_or(blue, |v| v.max(blue));
-
-            green = (&cap["color"] == "green")
-                .then(|| cap["color_count"].parse::<u32>().unwrap())
-                .map_or(green, |v| v.max(green));
-        }
+/// Add up the product of red, green and blue cubes of the smallest bag for each game.
+fn sum_powers(lines: LineSource<impl BufRead>) -> Result<u64> {
+    lines.parse::<Game>().ok_fold(0, |sum, game| {
+        let bag = game.minimum_bag();
+        let power = ["red", "green", "blue"]
+            .iter()
+            .map(|&color| bag.get(color).copied().unwrap_or(0) as u64)
+            .product::<u64>();
+        sum + power
+    })
+}
 
-        Ok(Self {
-            id: game_number,
-            red,
-            green,
-            blue,
-        })
-    }
+#[cfg(test)]
+fn compute_part1(raw_data: &str) -> Result<u64> {
+    sum_possible_ids(LineSource::new(raw_data.as_bytes()))
 }
 
-fn compute_part1(raw_data: &String) -> u32 {
-    raw_data
-        .lines()
-        .map(|line| Game::from_str(line).unwrap())
-        .filter_map(|game| game.is_possible(12, 13, 14).then(|| game.id))
-        .dedup_by(|a, b| a == b)
-        .sum()
+#[cfg(test)]
+fn compute_part2(raw_data: &str) -> Result<u64> {
+    sum_powers(LineSource::new(raw_data.as_bytes()))
 }
 
-fn compute_part2(raw_data: &String) -> u32 {
-    raw_data
-        .lines()
-        .map(|line| Game::from_str(line).unwrap())
-        .map(|game| game.red * game.green * game.blue)
-        .sum()
+fn main() -> Result<()> {
+    const INPUT: &str = "2023/day02/input.txt";
+
+    println!(
+        "Result part 1: {}",
+        sum_possible_ids(LineSource::open(INPUT)?)?
+    );
+    println!("Result part 2: {}", sum_powers(LineSource::open(INPUT)?)?);
+
+    // see why the games are impossible with some bag, like `red 12 green 13 blue 14`
+    let args = std::env::args().skip(1).collect_vec();
+    if !args.is_empty() {
+        let bag = args
+            .iter()
+            .tuples()
+            .map(|(color, count)| Ok((color.clone(), count.parse()?)))
+            .collect::<Result<Cubes>>()?;
+
+        for game in LineSource::open(INPUT)?.parse::<Game>() {
+            let game = game?;
+            for violation in game.violations(&bag) {
+                println!(
+                    "Game {}, draw {}: {} {} but only {} in the bag",
+                    game.id,
+                    violation.draw + 1,
+                    violation.drawn,
+                    violation.color,
+                    violation.available
+                );
+            }
+            println!(
+                "Game {} could happen with a chance of {:.3e}",
+                game.id,
+                game.probability(&bag)
+            );
+        }
+    }
+
+    Ok(())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day02/input.txt").expect("input file does not exists");
+common::examples! {
+    games: include_str!("../examples/1.txt") => {
+        compute_part1: 8,
+        compute_part2: 2286,
+    }
+}
+
+#[test]
+fn test_draws() -> Result<()> {
+    let game = "Game 7: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 purple".parse::<Game>()?;
+
+    assert_eq!(game.id, 7);
+    assert_eq!(game.draws.len(), 3);
+    assert_eq!(game.draws[1], bag(&[("red", 1), ("green", 2), ("blue", 6)]));
+    assert_eq!(
+        game.minimum_bag(),
+        bag(&[("red", 4), ("green", 2), ("blue", 6), ("purple", 2)])
+    );
+
+    let small = bag(&[("red", 4), ("green", 2), ("blue", 5)]);
+    let violations = game.violations(&small);
+    assert!(!game.is_possible(&small));
+    assert_eq!(
+        violations
+            .iter()
+            .map(|violation| (
+                violation.draw,
+                violation.color.as_str(),
+                violation.available
+            ))
+            .collect_vec(),
+        [(1, "blue", 5), (2, "purple", 0)]
+    );
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+    Ok(())
 }
 
 #[test]
-fn test_part1() -> Result<(), ()> {
-    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-
-    assert_eq!(8, compute_part1(&INPUT.to_string()));
+fn test_probability() -> Result<()> {
+    let game = "Game 1: 1 red; 1 red, 1 blue".parse::<Game>()?;
+
+    // 1/2 for the first draw, then 2 of the 1 red and 1 blue, which is the only way
+    let probability = game.probability(&bag(&[("red", 1), ("blue", 1)]));
+    assert!((probability - 0.5).abs() < 1e-12);
+
+    // 2/3, then 2 * 1 / C(3, 2)
+    let probability = game.probability(&bag(&[("red", 2), ("blue", 1)]));
+    assert!((probability - 4.0 / 9.0).abs() < 1e-12);
+
+    assert_eq!(game.probability(&bag(&[("red", 2)])), 0.0);
+
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<(), ()> {
-    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-
-    assert_eq!(2286, compute_part2(&INPUT.to_string()));
+fn test_parse_errors() {
+    let error = |line: &str| line.parse::<Game>().unwrap_err();
+
+    assert_eq!(error("Game 1: 3 blue, x red").span, 16..17);
+    assert_eq!(error("Game 1: 3 blue; 1 red, 2 red").span, 23..28);
+    assert!("Game x: 3 blue".parse::<Game>().is_err());
+}
+
+#[test]
+fn fuzz_games() -> Result<()> {
+    let corpus = [include_str!("../examples/1.txt")];
+
+    // what the queries see on the bag of part 1
+    let violations = |input: &str| {
+        let bag = bag(&[("red", 12), ("green", 13), ("blue", 14)]);
+        LineSource::new(input.as_bytes())
+            .parse::<Game>()
+            .ok_fold(0, |count, game| {
+                game.probability(&bag);
+                count + game.violations(&bag).len() as u64
+            })
+    };
+
+    common::fuzz_text(&corpus, 20_000, &[compute_part1, compute_part2, violations])?;
     Ok(())
 }
@@ -1,173 +1,123 @@
-use std::{collections::HashMap, fs};
+mod schematic;
 
-use itertools::Itertools;
+use std::fs;
 
-#[derive(PartialEq, Eq, Hash, Debug)]
-struct Point(usize, usize);
+use common::Result;
+use schematic::Schematic;
 
-impl Point {
-    fn shift(&self, s: (i32, i32)) -> Point {
-        Point(
-            (self.0 as i32 + s.0) as usize,
-            (self.1 as i32 + s.1) as usize,
-        )
-    }
-}
+fn compute_part1(raw_data: &String) -> Result<u64> {
+    let schematic = Schematic::parse(raw_data)?;
 
-type NumberIndex = usize;
-#[derive(Debug)]
-enum Entry {
-    Symbol(char),
-    Number(NumberIndex),
-}
-
-impl Entry {
-    pub fn is_symbol(&self) -> bool {
-        match self {
-            Entry::Symbol(_) => true,
-            _ => false,
-        }
-    }
+    Ok(schematic
+        .part_numbers()
+        .map(|number| u64::from(number.value))
+        .sum())
 }
 
-fn parse_data(raw_data: &String) -> (HashMap<Point, Entry>, Vec<u32>) {
-    let mut numbers = Vec::new();
-    let mut data: HashMap<Point, Entry> = HashMap::new();
-
-    raw_data.lines().enumerate().for_each(|(line_num, line)| {
-        let mut prev_has_number = false;
-
-        line.chars().enumerate().for_each(|(char_pos, ch)| {
-            match ch {
-                '.' => {
-                    prev_has_number = false;
-                }
-                '0'..='9' => {
-                    if prev_has_number {
-                        let last_number = numbers
-                            .last_mut()
-                            .expect("if the last was a number something is odd!");
-                        *last_number = *last_number * 10 + ch.to_digit(10).unwrap();
-                    } else {
-                        numbers.push(ch.to_digit(10).unwrap());
-                    };
-
-                    data.insert(Point(line_num, char_pos), Entry::Number(numbers.len() - 1));
-                    prev_has_number = true;
-                }
-                symbol => {
-                    data.insert(Point(line_num, char_pos), Entry::Symbol(symbol));
-                    prev_has_number = false;
-                }
-            };
+fn compute_part2(raw_data: &String) -> Result<u64> {
+    let schematic = Schematic::parse(raw_data)?;
+
+    // a gear is a `*` touching exactly two numbers, its ratio is their product
+    Ok(schematic
+        .gears('*', 2)
+        .map(|gear| {
+            schematic
+                .numbers_touching(gear)
+                .map(|number| u64::from(number.value))
+                .product::<u64>()
         })
-    });
-
-    (data, numbers)
+        .sum())
 }
 
-fn point_is_symbol(data: &HashMap<Point, Entry>, point: Point) -> bool {
-    data.get(&point).map_or(false, |e| e.is_symbol())
-}
+fn main() -> Result<()> {
+    let raw_data = fs::read_to_string("2023/day03/input.txt").expect("input file does not exists");
 
-const neighbors_shits: [(i32, i32); 8] = [
-    (-1, 0),
-    (-1, 1),
-    (0, 1),
-    (1, 1),
-    (1, 0),
-    (1, -1),
-    (0, -1),
-    (-1, -1),
-];
-
-fn compute_part1(raw_data: &String) -> u32 {
-    let (data, numbers) = parse_data(raw_data);
-
-    data.iter()
-        .filter_map(|(point, entry)| match entry {
-            Entry::Number(number_index) => neighbors_shits
-                .iter()
-                .any(|shift| point_is_symbol(&data, point.shift(*shift)))
-                .then(|| number_index),
-            _ => None,
-        })
-        .unique()
-        .map(|&index| numbers.get(index).unwrap())
-        .sum()
-}
+    println!("Result part 1: {}", compute_part1(&raw_data)?);
+    println!("Result part 2: {}", compute_part2(&raw_data)?);
+
+    if std::env::args().nth(1).as_deref() == Some("render") {
+        let schematic = Schematic::parse(&raw_data)?;
+        println!("{}", schematic.render());
+
+        let shared = (0..schematic.numbers().len())
+            .filter(|&number| schematic.symbols_touching(number).count() > 1)
+            .count();
+        println!(
+            "{} numbers, {} of them touching more than one of the {} symbols",
+            schematic.numbers().len(),
+            shared,
+            schematic.symbols().len()
+        );
+    }
 
-fn compute_part2(raw_data: &String) -> u32 {
-    let (data, numbers) = parse_data(raw_data);
-
-    // 1. get all the gears, by searching for '*'
-    // 2. get all neighbors of each gear and if they have two numbers return the gear ratio
-    // 3. sum all the products
-    data.iter()
-        .filter_map(|(point, entry)| match entry {
-            Entry::Symbol('*') => {
-                let neighbors = neighbors_shits
-                    .iter()
-                    .filter_map(|&shift| match data.get(&point.shift(shift)) {
-                        Some(Entry::Number(number_index)) => Some(*number_index),
-                        _ => None,
-                    })
-                    .unique()
-                    .collect::<Vec<usize>>();
-
-                if neighbors.len() > 1 {
-                    Some(
-                        neighbors
-                            .iter()
-                            .map(|&index| numbers[index])
-                            .product::<u32>(),
-                    )
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        })
-        .sum()
+    Ok(())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day03/input.txt").expect("input file does not exists");
-
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+common::examples! {
+    engine: include_str!("../examples/1.txt") => {
+        compute_part1: 4361,
+        compute_part2: 467835,
+    }
 }
 
 #[test]
-fn test_part1() -> Result<(), ()> {
-    const INPUT: &str = "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..";
-
-    assert_eq!(4361, compute_part1(&INPUT.to_string()));
+fn test_schematic() -> Result<()> {
+    let schematic = Schematic::parse(include_str!("../examples/1.txt"))?;
+    let number = |value| {
+        schematic
+            .numbers()
+            .iter()
+            .position(|number| number.value == value)
+            .unwrap()
+    };
+
+    assert_eq!(schematic.numbers()[number(617)].columns, 0..3);
+    assert_eq!(
+        schematic
+            .symbols_touching(number(617))
+            .map(|symbol| (symbol.kind, symbol.row, symbol.column))
+            .collect::<Vec<_>>(),
+        [('*', 4, 3)]
+    );
+    assert_eq!(schematic.symbols_touching(number(114)).count(), 0);
+    assert_eq!(schematic.part_numbers().count(), 8);
+
+    let star = schematic
+        .symbols()
+        .iter()
+        .position(|symbol| symbol.kind == '*');
+    assert_eq!(
+        schematic
+            .numbers_touching(star.unwrap())
+            .map(|number| number.value)
+            .collect::<Vec<_>>(),
+        [467, 35]
+    );
+    assert_eq!(schematic.gears('*', 2).count(), 2);
+    assert_eq!(schematic.gears('*', 1).count(), 1);
+
+    // numbers on the edges don't wrap around to the other side
+    let edges = Schematic::parse("1..\n..2\n*..")?;
+    assert_eq!(edges.part_numbers().count(), 0);
+
+    let rendered = schematic.render();
+    assert!(rendered.starts_with("\x1b[1;32m467\x1b[0m..\x1b[2m114\x1b[0m.."));
+    assert!(Schematic::parse("99999999999").is_err());
+
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<(), ()> {
-    const INPUT: &str = "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..";
-
-    assert_eq!(467835, compute_part2(&INPUT.to_string()));
+fn fuzz_schematics() -> Result<()> {
+    let render = |input: &String| {
+        let schematic = Schematic::parse(input)?;
+        Ok(schematic.render().len() as u64)
+    };
+
+    common::fuzz_text(
+        &[include_str!("../examples/1.txt")],
+        20_000,
+        &[compute_part1, compute_part2, render],
+    )?;
     Ok(())
 }
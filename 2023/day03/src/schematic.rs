@@ -0,0 +1,184 @@
+use std::{collections::HashMap, ops::Range};
+
+use common::{format_err, Result};
+use itertools::Itertools;
+
+const RESET: &str = "\x1b[0m";
+const PART: &str = "\x1b[1;32m";
+const NOT_PART: &str = "\x1b[2m";
+const SYMBOL: &str = "\x1b[1;33m";
+
+/// A number of the schematic, `columns` are the ones covered by its digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: u32,
+    pub row: usize,
+    pub columns: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: char,
+    pub row: usize,
+    pub column: usize,
+}
+
+/// The engine schematic with the numbers, the symbols and which of them touch each other,
+/// even on a diagonal.
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    rows: Vec<Vec<char>>,
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    symbols_of_number: Vec<Vec<usize>>,
+    numbers_of_symbol: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Result<Self> {
+        let rows = input
+            .lines()
+            .map(|line| line.trim_end().chars().collect_vec())
+            .collect_vec();
+
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+        for (row, line) in rows.iter().enumerate() {
+            let mut column = 0;
+            while column < line.len() {
+                let start = column;
+                while line.get(column).map_or(false, char::is_ascii_digit) {
+                    column += 1;
+                }
+
+                if start < column {
+                    let digits = line[start..column].iter().collect::<String>();
+                    let value = digits.parse().map_err(|_| {
+                        format_err!("number {} at {}:{} is too big", digits, row + 1, start + 1)
+                    })?;
+                    numbers.push(Number {
+                        value,
+                        row,
+                        columns: start..column,
+                    });
+                } else {
+                    if line[column] != '.' && !line[column].is_whitespace() {
+                        symbols.push(Symbol {
+                            kind: line[column],
+                            row,
+                            column,
+                        });
+                    }
+                    column += 1;
+                }
+            }
+        }
+
+        // look around every number, which is the same as looking around every symbol
+        let positions = symbols
+            .iter()
+            .enumerate()
+            .map(|(idx, symbol)| ((symbol.row, symbol.column), idx))
+            .collect::<HashMap<_, _>>();
+        let mut symbols_of_number = vec![Vec::new(); numbers.len()];
+        let mut numbers_of_symbol = vec![Vec::new(); symbols.len()];
+        for (idx, number) in numbers.iter().enumerate() {
+            let rows = number.row.saturating_sub(1)..=number.row + 1;
+            let columns = number.columns.start.saturating_sub(1)..=number.columns.end;
+
+            for position in rows.cartesian_product(columns) {
+                if let Some(&symbol) = positions.get(&position) {
+                    symbols_of_number[idx].push(symbol);
+                    numbers_of_symbol[symbol].push(idx);
+                }
+            }
+        }
+
+        Ok(Self {
+            rows,
+            numbers,
+            symbols,
+            symbols_of_number,
+            numbers_of_symbol,
+        })
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The symbols around the number at that index.
+    pub fn symbols_touching(&self, number: usize) -> impl Iterator<Item = &Symbol> + '_ {
+        self.symbols_of_number[number]
+            .iter()
+            .map(|&idx| &self.symbols[idx])
+    }
+
+    /// The numbers around the symbol at that index.
+    pub fn numbers_touching(&self, symbol: usize) -> impl Iterator<Item = &Number> + '_ {
+        self.numbers_of_symbol[symbol]
+            .iter()
+            .map(|&idx| &self.numbers[idx])
+    }
+
+    /// Numbers touching at least a symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> + '_ {
+        self.numbers
+            .iter()
+            .zip(&self.symbols_of_number)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// Indexes of the symbols of that kind touching exactly `neighbours` numbers.
+    pub fn gears(&self, kind: char, neighbours: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len()).filter(move |&idx| {
+            self.symbols[idx].kind == kind && self.numbers_of_symbol[idx].len() == neighbours
+        })
+    }
+
+    /// The schematic with ANSI colors: part numbers in green, the other numbers dimmed and the
+    /// symbols touching some number in yellow.
+    pub fn render(&self) -> String {
+        let mut styles = HashMap::new();
+        for (number, symbols) in self.numbers.iter().zip(&self.symbols_of_number) {
+            let style = if symbols.is_empty() { NOT_PART } else { PART };
+            for column in number.columns.clone() {
+                styles.insert((number.row, column), style);
+            }
+        }
+        for (symbol, numbers) in self.symbols.iter().zip(&self.numbers_of_symbol) {
+            if !numbers.is_empty() {
+                styles.insert((symbol.row, symbol.column), SYMBOL);
+            }
+        }
+
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                let mut rendered = String::new();
+                let mut current = None;
+                for (column, &ch) in line.iter().enumerate() {
+                    let style = styles.get(&(row, column)).copied();
+                    if style != current {
+                        if current.is_some() {
+                            rendered.push_str(RESET);
+                        }
+                        rendered.push_str(style.unwrap_or_default());
+                        current = style;
+                    }
+                    rendered.push(ch);
+                }
+                if current.is_some() {
+                    rendered.push_str(RESET);
+                }
+                rendered
+            })
+            .join("\n")
+    }
+}
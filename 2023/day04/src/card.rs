@@ -0,0 +1,73 @@
+use std::{collections::HashSet, ops::Range, str::FromStr};
+
+use common::{numbers, pattern, Context, ParseError, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub id: u32,
+    pub winner_numbers: HashSet<u32>,
+    pub game_numbers: HashSet<u32>,
+}
+
+impl Card {
+    /// How many of our numbers are winners.
+    pub fn matches(&self) -> usize {
+        self.winner_numbers.intersection(&self.game_numbers).count()
+    }
+
+    /// One point for the first match, then doubled for each of the others, which doesn't fit
+    /// in 64 bits past 64 matches.
+    pub fn points(&self) -> Result<u64> {
+        match self.matches() {
+            0 => Ok(0),
+            matches => u32::try_from(matches - 1)
+                .ok()
+                .and_then(|shift| 1_u64.checked_shl(shift))
+                .with_context(|| {
+                    format!("card {} has {} matches, too many points", self.id, matches)
+                }),
+        }
+    }
+}
+
+/// Numbers separated by spaces, `span` is where they are on the line.
+fn parse_numbers(line: &str, span: Range<usize>) -> Result<HashSet<u32>, ParseError> {
+    let field = &line[span.clone()];
+    if let Some((pos, ch)) = field
+        .char_indices()
+        .find(|&(_, ch)| !ch.is_ascii_digit() && !ch.is_whitespace())
+    {
+        return Err(ParseError::new(
+            span.start + pos..span.start + pos + ch.len_utf8(),
+            "a number",
+        ));
+    }
+
+    numbers(field)
+        .map(|number| number.map_err(|err| err.offset(span.start)))
+        .collect()
+}
+
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        let captures = pattern!("Card {id}: {winner_numbers} | {game_numbers}").captures(s)?;
+
+        let field = |name| captures.named_span(name).unwrap_or_default();
+        Ok(Self {
+            id: captures.name("id")?,
+            winner_numbers: parse_numbers(s, field("winner_numbers"))?,
+            game_numbers: parse_numbers(s, field("game_numbers"))?,
+        })
+    }
+}
+
+impl TryFrom<&str> for Card {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use common::{bail, format_err, Context, Result};
+
+use crate::card::Card;
+
+/// What to do when a card wins copies of cards that don't exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// the copies of missing cards are lost
+    Clamp,
+    /// it's an error
+    Error,
+}
+
+/// A card being scratched with all its copies, winning copies of the cards after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round {
+    pub card: u32,
+    pub copies: u64,
+    /// ids of the cards won, one copy of each for every copy of this card
+    pub won: Vec<u32>,
+}
+
+/// The result of scratching all the cards and the ones they won.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cascade {
+    /// how many of each card there are at the end, by id
+    pub copies: BTreeMap<u32, u64>,
+    /// one per card, in the order they were scratched
+    pub rounds: Vec<Round>,
+}
+
+impl Cascade {
+    /// Scratch the cards from the lowest id to the highest, a card with `n` matches wins a copy
+    /// of the cards with the `n` ids that follow its own.
+    pub fn run(cards: &[Card], overflow: Overflow) -> Result<Self> {
+        let mut copies = BTreeMap::new();
+        let mut matches = BTreeMap::new();
+        for card in cards {
+            if matches.insert(card.id, card.matches()).is_some() {
+                bail!("there is more than one card {}", card.id);
+            }
+            copies.insert(card.id, 1_u64);
+        }
+
+        let mut rounds = Vec::with_capacity(cards.len());
+        for (&card, &matches) in &matches {
+            let current = copies[&card];
+            let mut won = Vec::with_capacity(matches);
+
+            for id in (1..=matches as u64).map(|n| card as u64 + n) {
+                match u32::try_from(id).ok().and_then(|id| copies.get_mut(&id)) {
+                    Some(count) => {
+                        *count = count
+                            .checked_add(current)
+                            .with_context(|| format!("too many copies of card {}", id))?;
+                        won.push(id as u32);
+                    }
+                    None if overflow == Overflow::Clamp => {}
+                    None => bail!(
+                        "card {} wins a copy of card {}, which doesn't exist",
+                        card,
+                        id
+                    ),
+                }
+            }
+
+            rounds.push(Round {
+                card,
+                copies: current,
+                won,
+            });
+        }
+
+        Ok(Self { copies, rounds })
+    }
+
+    /// Same total as [`Cascade::run`], going through the cards as they come instead of
+    /// keeping them all. They must be sorted by id, only the copies won for the cards that
+    /// haven't come yet are kept.
+    pub fn count_streaming(
+        cards: impl Iterator<Item = Result<Card>>,
+        overflow: Overflow,
+    ) -> Result<u64> {
+        let missing = |id| format_err!("a copy of card {} was won, but it doesn't exist", id);
+
+        let mut pending = BTreeMap::<u32, u64>::new();
+        let mut previous = None;
+        let mut total = 0_u64;
+        for card in cards {
+            let card = card?;
+            if let Some(previous) = previous.filter(|&previous| previous >= card.id) {
+                bail!(
+                    "card {} comes after card {}, not sorted by id",
+                    card.id,
+                    previous
+                );
+            }
+            previous = Some(card.id);
+
+            // what's left before this card was won for cards that don't exist
+            let later = pending.split_off(&card.id);
+            if let Some(&id) = pending.keys().next() {
+                if overflow == Overflow::Error {
+                    return Err(missing(id as u64));
+                }
+            }
+            pending = later;
+
+            let copies = 1 + pending.remove(&card.id).unwrap_or(0);
+            total = total.checked_add(copies).context("too many cards")?;
+
+            for id in (1..=card.matches() as u64).map(|n| card.id as u64 + n) {
+                match u32::try_from(id) {
+                    Ok(id) => {
+                        let count = pending.entry(id).or_default();
+                        *count = count
+                            .checked_add(copies)
+                            .with_context(|| format!("too many copies of card {}", id))?;
+                    }
+                    Err(_) if overflow == Overflow::Clamp => {}
+                    Err(_) => return Err(missing(id)),
+                }
+            }
+        }
+
+        match pending.keys().next() {
+            Some(&id) if overflow == Overflow::Error => Err(missing(id as u64)),
+            _ => Ok(total),
+        }
+    }
+
+    /// All the cards we end up with, originals and copies.
+    pub fn total(&self) -> u64 {
+        self.copies.values().sum()
+    }
+}
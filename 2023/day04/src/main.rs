@@ -1,109 +1,144 @@
-use std::{collections::HashSet, fs};
+mod card;
+mod cascade;
 
-use regex::Regex;
+use std::io::BufRead;
 
-#[derive(Debug)]
-struct Card {
-    id: u32,
-    winner_numbers: HashSet<u32>,
-    game_numbers: HashSet<u32>,
-}
+use card::Card;
+use cascade::{Cascade, Overflow};
+use common::{Context, LineSource, OkIterator, Result};
 
-impl Into<Card> for &str {
-    fn into(self) -> Card {
-        let re =
-            Regex::new(r"Card\s+(?P<card_id>\d+): (?P<winner_numbers>.*) \| (?P<game_numbers>.*)$")
-                .unwrap();
-
-        let capture = re.captures_iter(self).next().unwrap();
-        let card_id = capture["card_id"].parse::<u32>().unwrap();
-        let winner_numbers = (&capture)["winner_numbers"]
-            .split(" ")
-            .filter_map(|s| s.parse::<u32>().ok())
-            .collect::<HashSet<u32>>();
-        let game_numbers = (&capture)["game_numbers"]
-            .split(" ")
-            .filter_map(|s| s.parse::<u32>().ok())
-            .collect::<HashSet<u32>>();
-
-        Card {
-            id: card_id,
-            winner_numbers: winner_numbers,
-            game_numbers: game_numbers,
-        }
-    }
+fn sum_points(lines: LineSource<impl BufRead>) -> Result<u64> {
+    lines.parse::<Card>().try_fold(0_u64, |sum, card| {
+        sum.checked_add(card?.points()?)
+            .context("the points don't fit in 64 bits")
+    })
 }
 
-fn parse(raw_data: &String) -> Vec<Card> {
-    raw_data
-        .lines()
-        .map(|line| line.into())
-        .collect::<Vec<Card>>()
+fn count_cards(lines: LineSource<impl BufRead>) -> Result<u64> {
+    // the puzzle says cards never win copies past the end of the table, but just in case
+    Cascade::count_streaming(lines.parse::<Card>(), Overflow::Clamp)
 }
 
-fn compute_part1(raw_data: &String) -> u32 {
-    let data = parse(raw_data);
-
-    data.iter()
-        .map(|card| {
-            let correct_count = card.winner_numbers.intersection(&card.game_numbers).count();
+#[cfg(test)]
+fn compute_part1(raw_data: &str) -> Result<u64> {
+    sum_points(LineSource::new(raw_data.as_bytes()))
+}
 
-            if correct_count == 0 {
-                0
-            } else {
-                2_u32.pow(correct_count as u32 - 1)
-            }
-        })
-        .sum()
+#[cfg(test)]
+fn compute_part2(raw_data: &str) -> Result<u64> {
+    count_cards(LineSource::new(raw_data.as_bytes()))
 }
 
-fn compute_part2(raw_data: &String) -> u32 {
-    let data = parse(raw_data);
-    let mut num_of_cards = vec![1; data.len()];
+fn main() -> Result<()> {
+    const INPUT: &str = "2023/day04/input.txt";
+
+    println!("Result part 1: {}", sum_points(LineSource::open(INPUT)?)?);
+    println!("Result part 2: {}", count_cards(LineSource::open(INPUT)?)?);
 
-    data.iter()
-        .map(|card| {
-            let correct_count = card.winner_numbers.intersection(&card.game_numbers).count();
+    if std::env::args().nth(1).as_deref() == Some("rounds") {
+        let cards = LineSource::open(INPUT)?.parse::<Card>().ok_collect_vec()?;
+        let cascade = Cascade::run(&cards, Overflow::Error)?;
+        for round in &cascade.rounds {
+            println!("Card {} x{} wins {:?}", round.card, round.copies, round.won);
+        }
+        println!("{} cards in total", cascade.total());
+    }
 
-            for n in 0..correct_count {
-                let index = (card.id + n as u32) as usize;
-                num_of_cards[index] += num_of_cards[(card.id - 1) as usize];
-            }
+    Ok(())
+}
 
-            num_of_cards[(card.id - 1) as usize]
-        })
-        .sum()
+common::examples! {
+    cards: include_str!("../examples/1.txt") => {
+        compute_part1: 13,
+        compute_part2: 30,
+    }
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day04/input.txt").expect("input file does not exists");
+#[test]
+fn test_cascade() -> common::Result<()> {
+    // ids don't need to start at 1 or follow each other
+    let cards = [
+        "Card 10: 1 2 | 1 2",
+        "Card 12: 5 | 5",
+        "Card 11: 1 | 1",
+        "Card 20: 3 | 4",
+    ]
+    .map(|line| Card::try_from(line).unwrap());
+
+    let cascade = Cascade::run(&cards, Overflow::Clamp)?;
+    assert_eq!(
+        cascade.copies.into_iter().collect::<Vec<_>>(),
+        [(10, 1), (11, 2), (12, 4), (20, 1)]
+    );
+    assert_eq!(cascade.rounds[1].copies, 2);
+    assert_eq!(cascade.rounds[1].won, [12]);
+    assert_eq!(cascade.rounds[2].won, []);
+
+    assert!(Cascade::run(&cards, Overflow::Error).is_err());
+    assert!(Cascade::run(&[cards[0].clone(), cards[0].clone()], Overflow::Clamp).is_err());
+
+    // the same without keeping the cards, as long as they are sorted
+    let stream = |cards: &[Card], overflow| {
+        Cascade::count_streaming(cards.iter().cloned().map(Ok), overflow)
+    };
+    let sorted = [&cards[0], &cards[2], &cards[1], &cards[3]].map(Card::clone);
+    assert_eq!(stream(&sorted, Overflow::Clamp)?, 8);
+    assert!(stream(&sorted, Overflow::Error).is_err());
+    assert!(stream(&sorted[..3], Overflow::Error).is_err());
+    assert_eq!(stream(&sorted[1..3], Overflow::Clamp)?, 3);
+    assert!(stream(&cards, Overflow::Clamp).is_err());
+
+    Ok(())
+}
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+#[test]
+fn test_card_errors() {
+    let error = |line: &str| line.parse::<Card>().unwrap_err();
+
+    assert_eq!(error("Card 1: 41 4x | 83").span, 12..13);
+    assert_eq!(error("Card 1: 41 | 99999999999").span, 13..24);
+    assert_eq!(error("Card 1: 41 | é").span, 13..15);
+    assert_eq!(
+        error("Card x: 41 | 83").expected,
+        "`id` to be a valid `u32`"
+    );
+    assert!(Card::try_from("Card 1: 41 48").is_err());
 }
 
 #[test]
-fn test_part1() -> Result<(), ()> {
-    const INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-
-    assert_eq!(13, compute_part1(&INPUT.to_string()));
+fn test_card_points() -> Result<()> {
+    let card = |matches: u32| {
+        let numbers = (1..=matches).map(|n| n.to_string()).collect::<Vec<_>>();
+        let numbers = numbers.join(" ");
+        Card::try_from(format!("Card 7: {} 99 | {} 98", numbers, numbers).as_str())
+    };
+
+    assert_eq!(card(0)?.points()?, 0);
+    assert_eq!(card(4)?.points()?, 8);
+    assert_eq!(card(64)?.points()?, 1 << 63);
+    assert_eq!(
+        card(65)?.points().unwrap_err().to_string(),
+        "card 7 has 65 matches, too many points"
+    );
+
+    // two of them are already too many
+    let numbers = (1..=64)
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = format!("Card 1: {0} | {0}\n", numbers);
+    let twice = line.repeat(2);
+    assert_eq!(sum_points(LineSource::new(line.as_bytes()))?, 1 << 63);
+    assert!(sum_points(LineSource::new(twice.as_bytes())).is_err());
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<(), ()> {
-    const INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-
-    assert_eq!(30, compute_part2(&INPUT.to_string()));
+fn fuzz_cards() -> Result<()> {
+    common::fuzz_text(
+        &[include_str!("../examples/1.txt")],
+        20_000,
+        &[compute_part1, compute_part2],
+    )?;
     Ok(())
 }
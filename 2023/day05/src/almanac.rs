@@ -0,0 +1,424 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
+
+use common::{bail, ensure, Context, OkIterator, Result};
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapEntry {
+    source_interval: (u64, u64),
+    destination_interval: (u64, u64),
+}
+
+impl MapEntry {
+    /// An entry converting `length` values from `source` on, which can't be empty and has to
+    /// end before `u64::MAX` on both sides.
+    pub fn new(source: u64, destination: u64, length: u64) -> Result<Self> {
+        ensure!(length > 0, "an entry must convert at least one value");
+        let last = |start: u64, side| {
+            start
+                .checked_add(length)
+                .map(|end| end - 1)
+                .with_context(|| {
+                    format!(
+                        "the {} of {} values from {} is too big",
+                        side, length, start
+                    )
+                })
+        };
+
+        Ok(MapEntry {
+            source_interval: (source, last(source, "source")?),
+            destination_interval: (destination, last(destination, "destination")?),
+        })
+    }
+
+    pub fn get_destination(&self, source: u64) -> Option<u64> {
+        if source >= self.source_interval.0 && source <= self.source_interval.1 {
+            return Some(self.destination_interval.0 + (source - self.source_interval.0));
+        }
+
+        None
+    }
+
+    /// The same entry, but going from the destination back to the source
+    pub fn inverse(&self) -> Self {
+        MapEntry {
+            source_interval: self.destination_interval,
+            destination_interval: self.source_interval,
+        }
+    }
+
+    /// Split a range on the part covered by this entry, already moved to the destination, and
+    /// the parts before and after it that the entry doesn't touch.
+    pub fn split_range(&self, range: &TrackedRange) -> (Option<TrackedRange>, Vec<TrackedRange>) {
+        let (source_start, source_end) = self.source_interval;
+        let start = range.start.max(source_start);
+        let end = range.end.min(source_end + 1);
+
+        if start >= end {
+            return (None, vec![range.clone()]);
+        }
+
+        let mut untouched = Vec::new();
+        if range.start < start {
+            untouched.push(TrackedRange {
+                start: range.start,
+                end: start,
+                origin: range.origin,
+            });
+        }
+        if end < range.end {
+            untouched.push(TrackedRange {
+                start: end,
+                end: range.end,
+                origin: range.origin + (end - range.start),
+            });
+        }
+
+        let destination = self.destination_interval.0 + (start - source_start);
+        let mapped = TrackedRange {
+            start: destination,
+            end: destination + (end - start),
+            origin: range.origin + (start - range.start),
+        };
+
+        (Some(mapped), untouched)
+    }
+}
+
+fn overlaps(a: (u64, u64), b: (u64, u64)) -> bool {
+    a.0 <= b.1 && b.0 <= a.1
+}
+
+/// Problems that make a map impossible to invert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// two entries convert the same source values
+    OverlappingSources { first: usize, second: usize },
+    /// two entries convert into the same destination values
+    OverlappingDestinations { first: usize, second: usize },
+    /// the entry converts into a value that isn't covered by any entry, so that value is also
+    /// reached by itself
+    AmbiguousDestination { entry: usize, value: u64 },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OverlappingSources { first, second } => {
+                write!(
+                    f,
+                    "entries {} and {} have overlapping sources",
+                    first, second
+                )
+            }
+            Self::OverlappingDestinations { first, second } => write!(
+                f,
+                "entries {} and {} have overlapping destinations",
+                first, second
+            ),
+            Self::AmbiguousDestination { entry, value } => write!(
+                f,
+                "value {} comes both from entry {} and from itself",
+                value, entry
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A range of values of any category, `start..end`, that remembers the value that ends up on
+/// `start` on the category where it started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedRange {
+    pub start: u64,
+    pub end: u64,
+    pub origin: u64,
+}
+
+impl TrackedRange {
+    pub fn new(start: u64, length: u64) -> Result<Self> {
+        let end = start
+            .checked_add(length)
+            .with_context(|| format!("the range of {} values from {} is too big", length, start))?;
+
+        Ok(Self {
+            start,
+            end,
+            origin: start,
+        })
+    }
+}
+
+/// The lowest value after going through the maps, and the one it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lowest {
+    pub value: u64,
+    pub origin: u64,
+}
+
+/// All the entries of a `X-to-Y map`, what they don't cover keeps the same number.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CategoryMap {
+    entries: Vec<MapEntry>,
+}
+
+impl CategoryMap {
+    pub fn new(entries: Vec<MapEntry>) -> Self {
+        Self { entries }
+    }
+
+    pub fn get(&self, source: u64) -> u64 {
+        self.entries
+            .iter()
+            .find_map(|entry| entry.get_destination(source))
+            .unwrap_or(source)
+    }
+
+    /// Check that every destination value comes from a single source, which is needed to be able
+    /// to invert the map.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        for (first, a) in self.entries.iter().enumerate() {
+            for (second, b) in self.entries.iter().enumerate().skip(first + 1) {
+                if overlaps(a.source_interval, b.source_interval) {
+                    return Err(ValidationError::OverlappingSources { first, second });
+                }
+                if overlaps(a.destination_interval, b.destination_interval) {
+                    return Err(ValidationError::OverlappingDestinations { first, second });
+                }
+            }
+        }
+
+        // the values that aren't a source of any entry map to themselves, so no entry can
+        // send anything there
+        let sources = self
+            .entries
+            .iter()
+            .map(|entry| entry.source_interval)
+            .sorted()
+            .collect_vec();
+        for (entry, map_entry) in self.entries.iter().enumerate() {
+            let (mut value, end) = map_entry.destination_interval;
+            for &(start, last) in &sources {
+                if start > value {
+                    break;
+                }
+                value = value.max(last.saturating_add(1));
+            }
+
+            if value <= end {
+                return Err(ValidationError::AmbiguousDestination { entry, value });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The map that converts the destination values back into their source.
+    pub fn inverse(&self) -> Result<CategoryMap, ValidationError> {
+        self.validate()?;
+
+        Ok(CategoryMap {
+            entries: self.entries.iter().map(MapEntry::inverse).collect(),
+        })
+    }
+
+    /// Move whole ranges through the map, splitting them where the entries start and end.
+    pub fn map_ranges(&self, mut ranges: Vec<TrackedRange>) -> Vec<TrackedRange> {
+        let mut mapped = Vec::with_capacity(ranges.len());
+
+        for entry in &self.entries {
+            let mut untouched = Vec::with_capacity(ranges.len());
+
+            for range in &ranges {
+                let (moved, rest) = entry.split_range(range);
+                mapped.extend(moved);
+                untouched.extend(rest);
+            }
+
+            ranges = untouched;
+        }
+
+        // what isn't covered by any entry keeps the same number
+        mapped.extend(ranges);
+        mapped
+    }
+
+    /// A single map that does the same as going through this map and then through `next`.
+    pub fn then(&self, next: &CategoryMap) -> Result<CategoryMap> {
+        let everything = vec![TrackedRange {
+            start: 0,
+            end: u64::MAX,
+            origin: 0,
+        }];
+
+        let entries = next
+            .map_ranges(self.map_ranges(everything))
+            .into_iter()
+            .filter(|range| range.start < range.end && range.start != range.origin)
+            .map(|range| MapEntry::new(range.origin, range.start, range.end - range.start))
+            .ok_collect_vec()?
+            .into_iter()
+            .sorted_by_key(|entry| entry.source_interval.0)
+            .collect_vec();
+
+        Ok(CategoryMap { entries })
+    }
+}
+
+/// The seeds and all the `X-to-Y` maps, which make a graph between the categories.
+#[derive(Debug, Default)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    maps: HashMap<String, Vec<(String, CategoryMap)>>,
+}
+
+impl Almanac {
+    pub fn new(seeds: Vec<u64>) -> Self {
+        Self {
+            seeds,
+            maps: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, from: &str, to: &str, map: CategoryMap) {
+        self.maps
+            .entry(from.to_string())
+            .or_default()
+            .push((to.to_string(), map));
+    }
+
+    /// The maps to go through to convert a category into another, with the fewest steps.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&CategoryMap>> {
+        let mut previous: HashMap<&str, (&str, &CategoryMap)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut current = to;
+                while let Some(&(source, map)) = previous.get(current) {
+                    path.push(map);
+                    current = source;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+
+            for (destination, map) in self.maps.get(category).into_iter().flatten() {
+                if destination != from && !previous.contains_key(destination.as_str()) {
+                    previous.insert(destination, (category, map));
+                    queue.push_back(destination);
+                }
+            }
+        }
+
+        bail!("there is no way to convert `{}` into `{}`", from, to)
+    }
+
+    /// The maps to convert a category back into another one, like location into seed, by
+    /// inverting the maps that go the other way around.
+    pub fn reverse_path(&self, from: &str, to: &str) -> Result<Vec<CategoryMap>> {
+        self.path(to, from)?
+            .into_iter()
+            .rev()
+            .map(|map| {
+                map.inverse()
+                    .with_context(|| format!("can't convert `{}` back into `{}`", from, to))
+            })
+            .ok_collect_vec()
+    }
+
+    /// Convert a single value, like soil 42 into humidity.
+    pub fn convert(&self, from: &str, to: &str, value: u64) -> Result<u64> {
+        Ok(self
+            .path(from, to)?
+            .into_iter()
+            .fold(value, |value, map| map.get(value)))
+    }
+
+    /// Precompute a single map that converts from a category to another.
+    pub fn compose(&self, from: &str, to: &str) -> Result<CategoryMap> {
+        self.path(from, to)?
+            .into_iter()
+            .try_fold(CategoryMap::default(), |composed, map| composed.then(map))
+    }
+
+    /// Convert a single value back, like location 46 into seed.
+    pub fn convert_back(&self, from: &str, to: &str, value: u64) -> Result<u64> {
+        Ok(self
+            .reverse_path(from, to)?
+            .iter()
+            .fold(value, |value, map| map.get(value)))
+    }
+
+    pub fn lowest_for_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: Vec<TrackedRange>,
+    ) -> Result<Option<Lowest>> {
+        let lowest = self
+            .path(from, to)?
+            .into_iter()
+            .fold(ranges, |ranges, map| map.map_ranges(ranges))
+            .into_iter()
+            .filter(|range| range.start < range.end)
+            .min_by_key(|range| range.start)
+            .map(|range| Lowest {
+                value: range.start,
+                origin: range.origin,
+            });
+
+        Ok(lowest)
+    }
+
+    /// Same as [`Almanac::lowest_for_ranges`] but searching from the lowest destination values
+    /// backwards, until reaching one of the ranges.
+    pub fn lowest_for_ranges_backward(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: Vec<TrackedRange>,
+    ) -> Result<Option<Lowest>> {
+        let everything = vec![TrackedRange {
+            start: 0,
+            end: u64::MAX,
+            origin: 0,
+        }];
+
+        // every piece is a range of source values, with the destination of its first value
+        let pieces = self
+            .reverse_path(to, from)?
+            .iter()
+            .fold(everything, |pieces, map| map.map_ranges(pieces))
+            .into_iter()
+            .sorted_by_key(|piece| piece.origin);
+
+        let mut lowest: Option<Lowest> = None;
+        for piece in pieces {
+            if lowest.map_or(false, |lowest| lowest.value <= piece.origin) {
+                break;
+            }
+
+            for range in &ranges {
+                let start = range.start.max(piece.start);
+                if start < range.end.min(piece.end) {
+                    let value = piece.origin + (start - piece.start);
+                    if lowest.map_or(true, |lowest| value < lowest.value) {
+                        lowest = Some(Lowest {
+                            value,
+                            origin: range.origin + (start - range.start),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(lowest)
+    }
+}
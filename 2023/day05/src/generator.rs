@@ -0,0 +1,69 @@
+use common::Rng;
+use itertools::Itertools;
+
+/// The categories of a real almanac, each one is mapped into the next.
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// Values up to here are shuffled around by the maps, the ones above are left as they are.
+const SPAN: u64 = 1 << 32;
+
+/// A random almanac with `size` entries on each map and `size / 4 + 1` seed ranges.
+///
+/// Every map cuts `0..2^32` into pieces and lays them out again in another order, so all of
+/// them can be inverted like the real ones.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+
+    let seeds = (0..size / 4 + 1)
+        .flat_map(|_| {
+            let start = rng.below(SPAN);
+            [start, rng.range(1..(SPAN / size as u64).max(2))]
+        })
+        .join(" ");
+    let mut almanac = format!("seeds: {}\n", seeds);
+
+    for (from, to) in CATEGORIES.iter().tuple_windows() {
+        almanac.push_str(&format!("\n{}-to-{} map:\n", from, to));
+        for (destination, source, length) in shuffled_pieces(rng, size) {
+            almanac.push_str(&format!("{} {} {}\n", destination, source, length));
+        }
+    }
+
+    almanac
+}
+
+/// `0..SPAN` cut in `pieces` pieces, as `(destination, source, length)` in a random order.
+fn shuffled_pieces(rng: &mut Rng, pieces: usize) -> Vec<(u64, u64, u64)> {
+    let mut cuts = vec![0, SPAN];
+    while cuts.len() < pieces + 1 {
+        cuts.push(rng.range(1..SPAN));
+        cuts.sort_unstable();
+        cuts.dedup();
+    }
+
+    let mut sources = cuts
+        .iter()
+        .tuple_windows()
+        .map(|(&start, &end)| (start, end - start))
+        .collect_vec();
+    rng.shuffle(&mut sources);
+
+    let mut destination = 0;
+    sources
+        .into_iter()
+        .map(|(source, length)| {
+            let piece = (destination, source, length);
+            destination += length;
+            piece
+        })
+        .collect()
+}
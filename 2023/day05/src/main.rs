@@ -1,222 +1,271 @@
 #![feature(iter_advance_by)]
 
-use std::{fs, str::Lines};
+use std::fs;
 
-use common::parse_line_numbers;
+use almanac::{Almanac, CategoryMap, MapEntry, TrackedRange};
+use common::*;
 use itertools::Itertools;
 
-#[derive(Debug)]
-struct MapEntry {
-    source_interval: (u64, u64),
-    destination_interval: (u64, u64),
-}
+mod almanac;
+mod generator;
+
+fn parse(raw_data: &String) -> Result<Almanac> {
+    let sections = Sections::new(raw_data);
+
+    // the seeds are at the top, on a block of their own
+    let seeds = sections
+        .blocks()
+        .first()
+        .and_then(|block| block.lines().first())
+        .and_then(|line| line.strip_prefix("seeds:"))
+        .context("missing seeds")?;
+    let mut almanac = Almanac::new(numbers(seeds).ok_collect_vec()?);
+
+    // all the other sections are maps between two categories, in any order
+    let header = Pattern::new("{from}-to-{to} map")?;
+    for (name, block) in sections.named() {
+        let (from, to) = header
+            .parse::<(String, String)>(name)
+            .on_line(block.line_index(), block.lines()[0])?;
+
+        let entries = block
+            .body()
+            .iter()
+            .map(|line| {
+                let [destination, source, length] = numbers_array(line)?;
+                MapEntry::new(source, destination, length)
+            })
+            .ok_enumerate_context(|idx| format!("entry {} of the `{}`", idx + 1, name))
+            .ok_collect_vec()?;
 
-impl MapEntry {
-    pub fn new(source: u64, destination: u64, length: u64) -> Self {
-        MapEntry {
-            source_interval: (source, source + length - 1),
-            destination_interval: (destination, destination + length - 1),
-        }
+        almanac.insert(&from, &to, CategoryMap::new(entries));
     }
 
-    pub fn get_destination(&self, source: u64) -> Option<u64> {
-        if source >= self.source_interval.0 && source <= self.source_interval.1 {
-            return Some(self.destination_interval.0 + (source - self.source_interval.0));
-        }
-
-        None
-    }
+    Ok(almanac)
 }
 
-#[derive(Debug)]
-struct Almanac {
-    seeds: Vec<u64>,
-    seed_to_soil: Vec<MapEntry>,
-    soil_to_fertilizer: Vec<MapEntry>,
-    fertilizer_to_water: Vec<MapEntry>,
-    water_to_light: Vec<MapEntry>,
-    light_to_temperature: Vec<MapEntry>,
-    temperature_to_humidity: Vec<MapEntry>,
-    humidity_to_location: Vec<MapEntry>,
-}
+fn compute_part1(raw_data: &String) -> Result<u64> {
+    let data = parse(raw_data)?;
+    let seed_to_location = data.compose("seed", "location")?;
 
-impl Almanac {
-    fn source_to_destination(&self, maps: &Vec<MapEntry>, source: u64) -> u64 {
-        for entry in maps {
-            let mapped = entry.get_destination(source);
+    data.seeds
+        .iter()
+        .map(|&seed| seed_to_location.get(seed))
+        .min()
+        .context("there are no seeds")
+}
 
-            if mapped.is_some() {
-                return mapped.unwrap();
-            }
-        }
+fn seed_ranges(data: &Almanac) -> Result<Vec<TrackedRange>> {
+    data.seeds
+        .iter()
+        .tuples()
+        .map(|(&start, &length)| TrackedRange::new(start, length))
+        .ok_collect_vec()
+}
 
-        source
-    }
+fn compute_part2(raw_data: &String) -> Result<u64> {
+    let data = parse(raw_data)?;
 
-    pub fn lowest_seed_number(&self, seeds: &Vec<u64>) -> u64 {
-        seeds
-            .iter()
-            .map(|&seed| {
-                let soil = self.source_to_destination(&self.seed_to_soil, seed);
-                let fertilizer = self.source_to_destination(&self.soil_to_fertilizer, soil);
-                let water = self.source_to_destination(&self.fertilizer_to_water, fertilizer);
-                let light = self.source_to_destination(&self.water_to_light, water);
-                let temperature = self.source_to_destination(&self.light_to_temperature, light);
-                let humidity =
-                    self.source_to_destination(&self.temperature_to_humidity, temperature);
-
-                self.source_to_destination(&self.humidity_to_location, humidity)
-            })
-            .min()
-            .unwrap()
-    }
+    let lowest = data
+        .lowest_for_ranges("seed", "location", seed_ranges(&data)?)?
+        .context("there are no seed ranges")?;
+    Ok(lowest.value)
 }
 
-/// Parse the next section
-fn parse_section(lines_iter: &mut Lines) -> Vec<MapEntry> {
-    // ignore the first line, which contains the section name
-    lines_iter.next();
+fn main() -> Result<()> {
+    // `--generate <seed> <size>` prints a random almanac instead
+    if let Some((mut rng, size)) = generate_request(std::env::args().skip(1))? {
+        print!("{}", generator::generate(&mut rng, size));
+        return Ok(());
+    }
 
-    let mut map_entries = Vec::new();
+    let raw_data = fs::read_to_string("2023/day05/input.txt").expect("input file does not exists");
 
-    while let Some(line) = lines_iter.next() {
-        // break when we find an empty line, this means that we reached to the end of the section
-        if line.is_empty() {
-            break;
-        }
+    println!("Result part 1: {}", compute_part1(&raw_data)?);
+    println!("Result part 2: {}", compute_part2(&raw_data)?);
+
+    // searching backwards from the lowest locations gets to the same place, but only when all
+    // the maps can be inverted
+    let data = parse(&raw_data)?;
+    let lowest = data
+        .lowest_for_ranges_backward("seed", "location", seed_ranges(&data)?)
+        .unwrap_or_else(|err| {
+            println!("Can't search backwards: {:#}", err);
+            None
+        });
+    if let Some(lowest) = lowest {
+        println!("Lowest location comes from seed {}", lowest.origin);
+    }
 
-        // parse number and created a new entry
-        let captures = parse_line_numbers(line);
-        map_entries.push(MapEntry::new(captures[1], captures[0], captures[2]));
+    // any other conversion can be asked for, like `soil humidity 42` or `location seed 46`
+    if let [from, to, value] = &std::env::args().skip(1).collect_vec()[..] {
+        let value = value
+            .parse()
+            .with_context(|| format!("`{}` isn't a value", value))?;
+        let converted = data
+            .convert(from, to, value)
+            .or_else(|_| data.convert_back(from, to, value))?;
+        println!("{} {} is {} {}", from, value, to, converted);
     }
 
-    map_entries
+    Ok(())
 }
 
-fn parse(raw_data: &String) -> Almanac {
-    let mut lines = raw_data.lines();
-
-    // the input follows the same structure as the example, so at the top we have the seeds
-    let seeds: Vec<u64> = parse_line_numbers(lines.next().unwrap());
-
-    // skip empty line after the seeds section
-    lines.next();
-
-    // since all the sections follows the same order there is no need to create more dynamic code
-    Almanac {
-        seeds,
-        seed_to_soil: parse_section(&mut lines),
-        soil_to_fertilizer: parse_section(&mut lines),
-        fertilizer_to_water: parse_section(&mut lines),
-        water_to_light: parse_section(&mut lines),
-        light_to_temperature: parse_section(&mut lines),
-        temperature_to_humidity: parse_section(&mut lines),
-        humidity_to_location: parse_section(&mut lines),
+common::examples! {
+    seeds: include_str!("../examples/1.txt") => {
+        compute_part1: 35,
+        compute_part2: 46,
     }
 }
 
-fn compute_part1(raw_data: &String) -> u64 {
-    let data = parse(raw_data);
+#[test]
+fn ranges_match_seed_by_seed() -> Result<()> {
+    let data = parse(&include_str!("../examples/1.txt").to_string())?;
+    let lowest = data
+        .lowest_for_ranges("seed", "location", seed_ranges(&data)?)?
+        .unwrap();
 
-    data.lowest_seed_number(&data.seeds)
+    let seeds = seed_ranges(&data)?
+        .iter()
+        .flat_map(|range| range.start..range.end)
+        .collect_vec();
+    let by_seed = seeds
+        .iter()
+        .map(|&seed| data.convert("seed", "location", seed))
+        .ok_collect_vec()?;
+
+    assert_eq!(
+        lowest,
+        almanac::Lowest {
+            value: 46,
+            origin: 82
+        }
+    );
+    assert_eq!(by_seed.iter().min(), Some(&lowest.value));
+    assert_eq!(data.convert("seed", "location", lowest.origin)?, 46);
+    Ok(())
 }
 
-fn compute_part2(raw_data: &String) -> u64 {
-    let data = parse(raw_data);
+#[test]
+fn convert_between_any_categories() -> Result<()> {
+    let data = parse(&include_str!("../examples/1.txt").to_string())?;
+
+    // soil 81 -> fertilizer 81 -> water 81 -> light 74 -> temperature 78 -> humidity 78
+    assert_eq!(data.convert("soil", "humidity", 81)?, 78);
+    assert_eq!(data.convert("water", "water", 7)?, 7);
+    assert!(data.convert("location", "seed", 46).is_err());
+
+    let soil_to_location = data.compose("soil", "location")?;
+    for soil in 0..200 {
+        assert_eq!(
+            soil_to_location.get(soil),
+            data.convert("soil", "location", soil)?
+        );
+    }
 
-    let seeds = data
-        .seeds
-        .iter()
-        .tuples()
-        .flat_map(|(&start, &length)| (start..(start + length)))
-        .collect_vec();
+    Ok(())
+}
 
-    data.lowest_seed_number(&seeds)
+#[test]
+fn search_backward_from_the_lowest_location() -> Result<()> {
+    let data = parse(&include_str!("../examples/1.txt").to_string())?;
+
+    assert_eq!(
+        data.lowest_for_ranges_backward("seed", "location", seed_ranges(&data)?)?,
+        data.lowest_for_ranges("seed", "location", seed_ranges(&data)?)?
+    );
+    assert_eq!(data.convert_back("location", "seed", 46)?, 82);
+    for seed in 0..200 {
+        let location = data.convert("seed", "location", seed)?;
+        assert_eq!(data.convert_back("location", "seed", location)?, seed);
+    }
+
+    Ok(())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day05/input.txt").expect("input file does not exists");
+#[test]
+fn ambiguous_maps_cannot_be_inverted() -> Result<()> {
+    use almanac::ValidationError;
+
+    let overlapping = CategoryMap::new(vec![MapEntry::new(0, 10, 5)?, MapEntry::new(3, 20, 5)?]);
+    assert_eq!(
+        overlapping.inverse(),
+        Err(ValidationError::OverlappingSources {
+            first: 0,
+            second: 1
+        })
+    );
+
+    let swapped = CategoryMap::new(vec![MapEntry::new(0, 10, 5)?, MapEntry::new(10, 0, 5)?]);
+    assert!(swapped.inverse().is_ok());
+
+    let moved = CategoryMap::new(vec![MapEntry::new(0, 10, 5)?, MapEntry::new(10, 12, 5)?]);
+    assert_eq!(
+        moved.inverse(),
+        Err(ValidationError::OverlappingDestinations {
+            first: 0,
+            second: 1
+        })
+    );
+
+    let shifted = CategoryMap::new(vec![MapEntry::new(0, 2, 5)?]);
+    assert_eq!(
+        shifted.inverse(),
+        Err(ValidationError::AmbiguousDestination { entry: 0, value: 5 })
+    );
+    Ok(())
+}
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+#[test]
+fn entries_must_fit() -> Result<()> {
+    assert!(MapEntry::new(0, 0, 0).is_err());
+    assert!(MapEntry::new(u64::MAX, 0, 1).is_err());
+    assert!(MapEntry::new(0, u64::MAX - 2, 3).is_err());
+    assert!(MapEntry::new(u64::MAX - 3, 0, 3).is_ok());
+    assert!(TrackedRange::new(u64::MAX, 1).is_err());
+    assert_eq!(TrackedRange::new(7, 0)?.end, 7);
+
+    let error = parse(&"seeds: 1 2\n\nseed-to-soil map:\n0 0 0\n".to_string()).unwrap_err();
+    assert_eq!(
+        format!("{:#}", error),
+        "entry 1 of the `seed-to-soil map`: an entry must convert at least one value"
+    );
+    Ok(())
 }
 
 #[test]
-fn test_part1() -> Result<(), ()> {
-    const INPUT: &str = "seeds: 79 14 55 13
-
-seed-to-soil map:
-50 98 2
-52 50 48
-
-soil-to-fertilizer map:
-0 15 37
-37 52 2
-39 0 15
-
-fertilizer-to-water map:
-49 53 8
-0 11 42
-42 0 7
-57 7 4
-
-water-to-light map:
-88 18 7
-18 25 70
-
-light-to-temperature map:
-45 77 23
-81 45 19
-68 64 13
-
-temperature-to-humidity map:
-0 69 1
-1 0 69
-
-humidity-to-location map:
-60 56 37
-56 93 4";
-
-    assert_eq!(35, compute_part1(&INPUT.to_string()));
+fn solve_generated_almanacs() -> Result<()> {
+    let input = generate_reproducible(generator::generate, 5, 20)?;
+
+    let data = parse(&input)?;
+    let by_seed = data
+        .seeds
+        .iter()
+        .map(|&seed| data.convert("seed", "location", seed))
+        .ok_collect_vec()?;
+    assert_eq!(compute_part1(&input)?, *by_seed.iter().min().unwrap());
+
+    // all the maps can be inverted, so searching backwards works too
+    assert_eq!(data.reverse_path("location", "seed")?.len(), 7);
+    assert_eq!(
+        data.lowest_for_ranges_backward("seed", "location", seed_ranges(&data)?)?,
+        data.lowest_for_ranges("seed", "location", seed_ranges(&data)?)?
+    );
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<(), ()> {
-    const INPUT: &str = "seeds: 79 14 55 13
-
-seed-to-soil map:
-50 98 2
-52 50 48
-
-soil-to-fertilizer map:
-0 15 37
-37 52 2
-39 0 15
-
-fertilizer-to-water map:
-49 53 8
-0 11 42
-42 0 7
-57 7 4
-
-water-to-light map:
-88 18 7
-18 25 70
-
-light-to-temperature map:
-45 77 23
-81 45 19
-68 64 13
-
-temperature-to-humidity map:
-0 69 1
-1 0 69
-
-humidity-to-location map:
-60 56 37
-56 93 4";
-
-    assert_eq!(46, compute_part2(&INPUT.to_string()));
+fn fuzz_almanacs() -> Result<()> {
+    let backward = |input: &String| {
+        let data = parse(input)?;
+        let lowest = data.lowest_for_ranges_backward("seed", "location", seed_ranges(&data)?)?;
+        Ok(lowest.map_or(0, |lowest| lowest.value))
+    };
+
+    common::fuzz_text(
+        &[include_str!("../examples/1.txt")],
+        20_000,
+        &[compute_part1, compute_part2, backward],
+    )?;
     Ok(())
 }
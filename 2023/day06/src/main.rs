@@ -1,8 +1,8 @@
 #![feature(iter_advance_by)]
 
-use std::{fs, ops::Div, str::Lines, vec};
+use std::fs;
 
-use common::parse_line_numbers;
+use common::{ensure, joined_number, numbers, Context, OkIterator, Result};
 use itertools::Itertools;
 
 // distanceTraveled = (raceTime - buttonPressTime) * buttonPressTime
@@ -20,85 +20,166 @@ impl Race {
     }
 }
 
-fn parse(raw_data: &String) -> Vec<Race> {
-    let mut lines = raw_data.lines();
+fn parse(raw_data: &String) -> Result<Vec<Race>> {
+    let mut lines = raw_data.lines().filter(|line| !line.trim().is_empty());
+
+    let times = numbers(lines.next().context("missing the times")?).ok_collect_vec()?;
+    let distances = numbers(lines.next().context("missing the distances")?).ok_collect_vec()?;
+    ensure!(
+        times.len() == distances.len(),
+        "there are {} times but {} distances",
+        times.len(),
+        distances.len()
+    );
+
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| Race::new(time, distance))
+        .collect_vec())
+}
 
-    let times = parse_line_numbers(lines.next().unwrap());
-    let distances = parse_line_numbers(lines.next().unwrap());
+/// Largest `x` such that `x * x <= n`.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
 
-    times
-        .iter()
-        .enumerate()
-        .map(|(idx, &time)| Race::new(time, distances[idx]))
-        .collect_vec()
+    // Newton's method never goes below the root when starting above it
+    let mut x = 1 << ((128 - n.leading_zeros() + 1) / 2);
+    loop {
+        let next = (x + n / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
 }
 
+/// Number of ways to beat the record, computed with integers only so there are no rounding
+/// issues when the roots are exact or the numbers are too big for a `f64`.
 fn solver(race: &Race) -> u64 {
-    let half_time = race.time as f64 / 2_f64;
-    let sqrt_result = ((race.time.pow(2) - 4 * (race.distance + 1)) as f64).sqrt() / 2_f64;
-    let lower_bound = (half_time - sqrt_result).ceil();
-    let upper_bound = (half_time + sqrt_result).floor().min(race.time as f64);
+    let time = race.time as u128;
+    let distance = race.distance as u128;
+    let wins = |press: u128| press * (time - press) > distance;
+
+    // the best we can do is pressing for half of the race
+    if !wins(time / 2) {
+        return 0;
+    }
 
-    (upper_bound - lower_bound) as u64 + 1
+    // first guess from the quadratic formula, then fix it to be the first press that wins
+    let root = isqrt(time * time - 4 * distance);
+    let mut lowest = (time - root) / 2;
+    while !wins(lowest) {
+        lowest += 1;
+    }
+    while lowest > 0 && wins(lowest - 1) {
+        lowest -= 1;
+    }
+
+    // the winning presses are symmetric around the middle of the race
+    (time - 2 * lowest + 1) as u64
 }
 
-fn compute_part1(raw_data: &String) -> u64 {
-    let data = parse(raw_data);
+fn compute_part1(raw_data: &String) -> Result<u64> {
+    let data = parse(raw_data)?;
 
-    data.iter().map(solver).product()
+    data.iter()
+        .map(solver)
+        .try_fold(1_u64, u64::checked_mul)
+        .context("the product doesn't fit in 64 bits")
 }
 
-fn compute_part2(raw_data: &String) -> u64 {
-    let mut lines = raw_data.lines();
-
-    let time = lines
-        .next()
-        .map(|line| {
-            line.split(':')
-                .last()
-                .unwrap()
-                .replace(' ', "")
-                .parse::<u64>()
-                .unwrap()
-        })
-        .unwrap();
-
-    let distance = lines
-        .next()
-        .map(|line| {
-            line.split(':')
-                .last()
-                .unwrap()
-                .replace(' ', "")
-                .parse::<u64>()
-                .unwrap()
-        })
-        .unwrap();
-
-    solver(&Race { time, distance })
+fn compute_part2(raw_data: &String) -> Result<u64> {
+    // the kerning was bad, each line is a single number
+    let [time, distance] = raw_data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(joined_number::<u64>)
+        .ok_collect_array()?;
+
+    Ok(solver(&Race { time, distance }))
 }
 
-fn main() {
+fn main() -> Result<()> {
     let raw_data = fs::read_to_string("2023/day06/input.txt").expect("input file does not exists");
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+    println!("Result part 1: {}", compute_part1(&raw_data)?);
+    println!("Result part 2: {}", compute_part2(&raw_data)?);
+    Ok(())
+}
+
+common::examples! {
+    races: include_str!("../examples/1.txt") => {
+        compute_part1: 288,
+        compute_part2: 71503,
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(parse(&"Time: 7 15\nDistance: 9".to_string()).is_err());
+    assert!(parse(&"Time: 7 15".to_string()).is_err());
+    assert!(parse(&String::new()).is_err());
+    assert!(compute_part2(&"Time: 7".to_string()).is_err());
+    assert!(compute_part1(&"Time: 99999999999999999999\nDistance: 9".to_string()).is_err());
+
+    // blank lines around the races don't count
+    let input = format!("\n{}\n\n", include_str!("../examples/1.txt"));
+    assert_eq!(compute_part1(&input).unwrap(), 288);
+    assert_eq!(compute_part2(&input).unwrap(), 71503);
+}
+
+#[cfg(test)]
+fn brute_force(race: &Race) -> u64 {
+    (0..=race.time)
+        .filter(|press| press * (race.time - press) > race.distance)
+        .count() as u64
 }
 
 #[test]
-fn test_part1() -> Result<(), ()> {
-    const INPUT: &str = "Time:      7  15   30
-Distance:  9  40  200";
+fn test_solver_edges() {
+    // the record is exactly what the best press gives
+    assert_eq!(solver(&Race::new(10, 25)), 0);
+    assert_eq!(solver(&Race::new(10, 24)), 1);
+    assert_eq!(solver(&Race::new(30, 200)), 9);
+    assert_eq!(solver(&Race::new(0, 0)), 0);
+    assert_eq!(solver(&Race::new(1, 0)), 0);
+    assert_eq!(solver(&Race::new(2, 0)), 1);
+    assert_eq!(solver(&Race::new(u64::MAX, 0)), u64::MAX - 1);
+}
 
-    assert_eq!(compute_part1(&INPUT.to_string()), 288);
+#[test]
+fn test_solver_differential() -> Result<()> {
+    let generate = |rng: &mut common::Rng, size: usize| {
+        let time = rng.below(size as u64 * 10 + 1);
+        (time, rng.below(time * time / 4 + 10))
+    };
+    let shrink = |&(time, distance): &(u64, u64)| {
+        let shorter = common::shrink_number(time)
+            .into_iter()
+            .map(|time| (time, distance));
+        let closer = common::shrink_number(distance)
+            .into_iter()
+            .map(|distance| (time, distance));
+        shorter.chain(closer).collect_vec()
+    };
+
+    common::differential(
+        300,
+        generate,
+        shrink,
+        |&(time, distance)| solver(&Race::new(time, distance)),
+        |&(time, distance)| brute_force(&Race::new(time, distance)),
+    )?;
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<(), ()> {
-    const INPUT: &str = "Time:      7  15   30
-    Distance:  9  40  200";
+fn fuzz_races() -> Result<()> {
+    let corpus = [include_str!("../examples/1.txt")];
 
-    assert_eq!(compute_part2(&INPUT.to_string()), 71503);
+    common::fuzz_text(&corpus, 20_000, &[compute_part1, compute_part2])?;
     Ok(())
 }
@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+
+use common::Rng;
+use itertools::Itertools;
+
+const LABELS: [char; 13] = [
+    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+];
+
+/// How the five cards are grouped on each type of hand, from five of a kind to high card.
+const SHAPES: [&[usize]; 7] = [
+    &[5],
+    &[4, 1],
+    &[3, 2],
+    &[3, 1, 1],
+    &[2, 2, 1],
+    &[2, 1, 1, 1],
+    &[1, 1, 1, 1, 1],
+];
+
+/// There are no more different hands than this, it's also the most that can be asked for.
+pub const MAX_HANDS: usize = 13_usize.pow(5);
+
+/// `size` different hands with bids up to 1000, every type of hand being as likely as the
+/// others so the rare ones show up too.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut seen = HashSet::new();
+    let mut hands = String::new();
+
+    while seen.len() < size.min(MAX_HANDS) {
+        let mut labels = LABELS;
+        rng.shuffle(&mut labels);
+
+        let shape = rng.choose(&SHAPES);
+        let mut cards = shape
+            .iter()
+            .zip(labels)
+            .flat_map(|(&count, label)| [label].repeat(count))
+            .collect_vec();
+        rng.shuffle(&mut cards);
+
+        let cards = cards.into_iter().collect::<String>();
+        if seen.insert(cards.clone()) {
+            hands.push_str(&format!("{} {}\n", cards, rng.range(1..1001)));
+        }
+    }
+
+    hands
+}
@@ -1,208 +1,243 @@
 #![feature(iter_advance_by)]
 
+mod generator;
+mod report;
+mod rules;
+
 use std::fs;
 
 use itertools::Itertools;
 
 use common::*;
+use rules::Rules;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum Card {
-    N2,
-    N3,
-    N4,
-    N5,
-    N6,
-    N7,
-    N8,
-    N9,
-    T,
-    J,
-    Q,
-    K,
-    A,
+#[derive(Debug)]
+struct Entry {
+    cards: Vec<char>,
+    bid: u32,
 }
 
-/// A `None` means the card is a Joker
-type WeakCardVersion = Option<Card>;
-
-impl Card {
-    #[inline]
-    fn card_to_weak(self) -> WeakCardVersion {
-        (self != Self::J).then_some(self)
+impl Entry {
+    pub fn new(cards: Vec<char>, bid: u32) -> Self {
+        Self { cards, bid }
     }
 }
 
-impl TryFrom<char> for Card {
-    type Error = Error;
-
-    fn try_from(value: char) -> Result<Self> {
-        match value {
-            'A' => Ok(Self::A),
-            'K' => Ok(Self::K),
-            'Q' => Ok(Self::Q),
-            'J' => Ok(Self::J),
-            'T' => Ok(Self::T),
-            '9' => Ok(Self::N9),
-            '8' => Ok(Self::N8),
-            '7' => Ok(Self::N7),
-            '6' => Ok(Self::N6),
-            '5' => Ok(Self::N5),
-            '4' => Ok(Self::N4),
-            '3' => Ok(Self::N3),
-            '2' => Ok(Self::N2),
-            _ => bail!("invalid card given"),
-        }
-    }
+fn parse(raw_data: &String) -> Result<Vec<Entry>> {
+    raw_data
+        .lines()
+        .map(|line| {
+            let (cards, bid) = line.split_once(' ').context("invalid input given")?;
+            let bid: u32 = bid.parse().context("invalid bid value")?;
+            Ok(Entry::new(cards.chars().collect(), bid))
+        })
+        .ok_collect_vec()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum Hand {
-    HightCard,
-    OnePair,
-    TwoPairs,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
+/// Rank all the hands with the given rules and add up what each of them wins.
+fn total_winnings(entries: &[Entry], rules: &Rules) -> Result<u64> {
+    report::rank_entries(entries, rules)?
+        .iter()
+        .try_fold(0_u64, |total, hand| total.checked_add(hand.winnings))
+        .context("the total winnings don't fit in 64 bits")
 }
 
-trait GetHand {
-    fn hand(self) -> Hand;
+fn compute_part1(raw_data: &String) -> Result<u64> {
+    let data = parse(raw_data)?;
+
+    total_winnings(&data, &Rules::camel_cards())
 }
 
-impl GetHand for [Card; 5] {
-    fn hand(mut self) -> Hand {
-        self.sort();
-
-        // count the grouped cards
-        let mut counts = self
-            .iter()
-            .dedup_with_count()
-            .map(|(count, _)| count)
-            .collect_vec();
-
-        // now we use the counts to identify the hand
-        counts.sort_unstable();
-        counts.reverse();
-
-        match counts[..] {
-            [5] => Hand::FiveOfAKind,
-            [4, 1] => Hand::FourOfAKind,
-            [3, 2] => Hand::FullHouse,
-            [3, 1, 1] => Hand::ThreeOfAKind,
-            [2, 2, 1] => Hand::TwoPairs,
-            [2, 1, 1, 1] => Hand::OnePair,
-            [1, 1, 1, 1, 1] => Hand::HightCard,
-            _ => unreachable!(),
-        }
-    }
+fn compute_part2(raw_data: &String) -> Result<u64> {
+    let data = parse(raw_data)?;
+
+    total_winnings(&data, &Rules::camel_cards_with_jokers())
 }
 
-impl GetHand for [WeakCardVersion; 5] {
-    fn hand(mut self) -> Hand {
-        self.sort();
-
-        // now we remove the jokers, that will allow us to compare only the remaining cards
-        let mut counts = self
-            .iter()
-            .filter(|card| card.is_some())
-            .dedup_with_count()
-            .map(|(count, _)| count)
-            .collect_vec();
-
-        counts.sort_unstable();
-        counts.reverse();
-
-        match counts[..] {
-            [] | [_] => Hand::FiveOfAKind,
-            [_, 1] => Hand::FourOfAKind,
-            [_, 2] => Hand::FullHouse,
-            [_, 1, 1] => Hand::ThreeOfAKind,
-            [_, 2, 1] => Hand::TwoPairs,
-            [_, 1, 1, 1] => Hand::OnePair,
-            [1, 1, 1, 1, 1] => Hand::HightCard,
-            _ => unreachable!(),
+fn main() -> Result<()> {
+    // `--generate <seed> <size>` prints random hands instead
+    if let Some((mut rng, size)) = generate_request(std::env::args().skip(1))? {
+        print!("{}", generator::generate(&mut rng, size));
+        return Ok(());
+    }
+
+    let raw_data = fs::read_to_string("2023/day07/input.txt").expect("input file does not exists");
+
+    println!("Result part 1: {}", compute_part1(&raw_data)?);
+    println!("Result part 2: {}", compute_part2(&raw_data)?);
+
+    // `report` explains how each hand was ranked, `diff` how the jokers changed the ranking and
+    // other variants can be played too, like `23456789TJQKA 5 2J` for deuces and jokers wild
+    const USAGE: &str = "usage: [report | diff | <card order> <hand size> [<wild cards>]]";
+    let data = parse(&raw_data)?;
+    let args = std::env::args().skip(1).collect_vec();
+    match args.iter().map(String::as_str).collect_vec()[..] {
+        [] => {}
+        ["report"] => {
+            for (part, rules) in [Rules::camel_cards(), Rules::camel_cards_with_jokers()]
+                .iter()
+                .enumerate()
+            {
+                let ranked = report::rank_entries(&data, rules)?;
+                println!("\nPart {}:\n{}", part + 1, report::render_report(&ranked));
+            }
         }
+        ["diff"] => {
+            let part1 = report::rank_entries(&data, &Rules::camel_cards())?;
+            let part2 = report::rank_entries(&data, &Rules::camel_cards_with_jokers())?;
+            println!("\n{}", report::render_diff(&report::diff(&part1, &part2)));
+        }
+        [order, hand_size, ref wild @ ..] if wild.len() <= 1 => {
+            let hand_size = hand_size
+                .parse()
+                .with_context(|| format!("`{}` isn't a hand size\n{}", hand_size, USAGE))?;
+            let rules = Rules::new(order, hand_size)?.with_wild(wild.first().unwrap_or(&""))?;
+            let ranked = report::rank_entries(&data, &rules)?;
+            println!("\n{}", report::render_report(&ranked));
+        }
+        _ => bail!(USAGE),
     }
-}
 
-#[derive(Debug)]
-struct Entry {
-    cards: [Card; 5],
-    bid: u32,
+    Ok(())
 }
 
-impl Entry {
-    pub fn new(cards: [Card; 5], bid: u32) -> Self {
-        Self { cards, bid }
+common::examples! {
+    hands: include_str!("../examples/1.txt") => {
+        compute_part1: 6440,
+        compute_part2: 5905,
     }
 }
 
-fn parse(raw_data: &String) -> Result<Vec<Entry>> {
-    raw_data
-        .lines()
-        .map(|line| {
-            let (cards, bid) = line.split_once(' ').context("invalid input given")?;
-            let bid: u32 = bid.parse().expect("invalid big value");
-            let cards = cards.chars().map(Card::try_from).ok_collect_array()?;
-            Ok(Entry::new(cards, bid))
-        })
-        .ok_collect_vec()
+#[test]
+fn classify_with_any_rules() -> Result<()> {
+    let part1 = Rules::camel_cards();
+    let part2 = Rules::camel_cards_with_jokers();
+    let category = |rules: &Rules, hand: &str| -> Result<String> {
+        let cards = hand.chars().collect_vec();
+        Ok(rules.categories()[rules.classify(&cards)?].name.clone())
+    };
+
+    assert_eq!(category(&part1, "KTJJT")?, "two pairs");
+    assert_eq!(category(&part2, "KTJJT")?, "four of a kind");
+    assert_eq!(category(&part2, "JJJJJ")?, "five of a kind");
+    assert_eq!(category(&part2, "2345J")?, "one pair");
+    assert!(part1.classify(&['2', '3']).is_err());
+    assert!(part1.classify(&['2', '3', '4', '5', 'X']).is_err());
+
+    // jokers and deuces are wild on six card hands
+    let wild = Rules::new("23456789TJQKA", 6)?.with_wild("2J")?;
+    assert_eq!(wild.categories().len(), 11);
+    assert_eq!(category(&wild, "2J3456")?, "3+1+1+1");
+    assert_eq!(category(&wild, "2J33KK")?, "4+2");
+    assert!(
+        wild.key(&"2AAAAA".chars().collect_vec())? > wild.key(&"AAAA2K".chars().collect_vec())?
+    );
+
+    // with categories missing some hands can't be classified
+    let pairs_only = Rules::camel_cards().with_categories(vec![
+        rules::Category::new("one pair", &[2, 1, 1, 1]),
+        rules::Category::new("two pairs", &[2, 2, 1]),
+    ])?;
+    assert_eq!(category(&pairs_only, "AAKKQ")?, "two pairs");
+    assert!(pairs_only.classify(&['A', 'A', 'A', 'K', 'Q']).is_err());
+    assert!(pairs_only.classify(&['A', 'K', 'Q', 'J', 'T']).is_err());
+    assert!(Rules::camel_cards()
+        .with_categories(vec![rules::Category::new("pair", &[2])])
+        .is_err());
+
+    Ok(())
 }
 
-fn compute_part1(raw_data: &String) -> u32 {
-    let data = parse(raw_data).unwrap();
+#[test]
+fn test_report() -> Result<()> {
+    let data = parse(&include_str!("../examples/1.txt").to_string())?;
+    let part1 = report::rank_entries(&data, &Rules::camel_cards())?;
+    let part2 = report::rank_entries(&data, &Rules::camel_cards_with_jokers())?;
+
+    assert_eq!(
+        part2.iter().map(|hand| hand.cards.as_str()).collect_vec(),
+        ["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]
+    );
+    assert_eq!(part2[4].substitution.as_deref(), Some("KTTTT"));
+    assert_eq!(part2[4].category, "four of a kind");
+    assert_eq!(part2[1].substitution, None);
+    assert_eq!(part1.iter().map(|hand| hand.winnings).sum::<u64>(), 6440);
+    assert!(report::render_report(&part2).ends_with("total winnings: 5905"));
+
+    let changes = report::diff(&part1, &part2);
+    assert_eq!(changes[4].cards, "KTJJT");
+    assert_eq!(changes[4].moved(), 3);
+    assert_eq!(
+        report::render_diff(&changes).lines().last(),
+        Some("KTJJT #2 -> #5 (+3) two pairs -> four of a kind")
+    );
 
-    data.iter()
-        .map(|entry| (entry.cards.hand(), entry.cards, entry.bid))
-        .sorted()
-        .enumerate()
-        .map(|(idx, (_, _, bid))| bid * (idx as u32 + 1))
-        .sum()
+    Ok(())
 }
 
-fn compute_part2(raw_data: &String) -> u32 {
-    let data = parse(raw_data).unwrap();
+#[test]
+fn substitute_wild_cards() -> Result<()> {
+    let substitute = |rules: &Rules, hand: &str| -> Result<Option<String>> {
+        let cards = hand.chars().collect_vec();
+        Ok(rules
+            .substitute(&cards)?
+            .map(|cards| cards.into_iter().collect()))
+    };
+    let jokers = Rules::camel_cards_with_jokers();
+
+    assert_eq!(substitute(&jokers, "JJJJJ")?.as_deref(), Some("AAAAA"));
+    assert_eq!(substitute(&jokers, "2J3J4")?.as_deref(), Some("24344"));
+    assert_eq!(substitute(&jokers, "23456")?, None);
+
+    let wild = Rules::new("23456789TJQKA", 6)?.with_wild("2J")?;
+    assert_eq!(substitute(&wild, "2J33KK")?.as_deref(), Some("KK33KK"));
 
-    data.iter()
-        .map(|entry| {
-            let weak_cards = entry.cards.map(Card::card_to_weak);
-            (weak_cards.hand(), weak_cards, entry.bid)
-        })
-        .sorted()
-        .enumerate()
-        .map(|(idx, (_, _, bid))| bid * (idx as u32 + 1))
-        .sum()
+    Ok(())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day07/input.txt").expect("input file does not exists");
+#[test]
+fn winnings_past_u32() {
+    let input = format!("22223 {0}\n22222 {0}\n", u32::MAX);
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+    assert_eq!(compute_part1(&input).unwrap(), 3 * u32::MAX as u64);
 }
 
 #[test]
-fn test_part1() -> Result<()> {
-    const INPUT: &str = "32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483";
-
-    assert_eq!(compute_part1(&INPUT.to_string()), 6440);
+fn solve_generated_hands() -> Result<()> {
+    use std::collections::HashSet;
+
+    let input = generate_reproducible(generator::generate, 7, 200)?;
+
+    let data = parse(&input)?;
+    assert_eq!(data.iter().map(|entry| &entry.cards).unique().count(), 200);
+    assert!(compute_part1(&input)? > 0 && compute_part2(&input)? > 0);
+
+    // every type of hand shows up
+    let rules = Rules::camel_cards();
+    let categories = data
+        .iter()
+        .map(|entry| rules.classify(&entry.cards))
+        .ok_collect::<HashSet<_>>()?;
+    assert_eq!(categories.len(), rules.categories().len());
+
+    assert_eq!(generator::generate(&mut Rng::new(7), 0), "");
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<()> {
-    const INPUT: &str = "32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483";
-
-    assert_eq!(compute_part2(&INPUT.to_string()), 5905);
+fn fuzz_hands() -> Result<()> {
+    let diff = |input: &String| {
+        let data = parse(input)?;
+        let part1 = report::rank_entries(&data, &Rules::camel_cards())?;
+        let part2 = report::rank_entries(&data, &Rules::camel_cards_with_jokers())?;
+        let diff = report::render_diff(&report::diff(&part1, &part2));
+        Ok((report::render_report(&part2).len() + diff.len()) as u64)
+    };
+
+    common::fuzz_text(
+        &[include_str!("../examples/1.txt")],
+        20_000,
+        &[compute_part1, compute_part2, diff],
+    )?;
     Ok(())
 }
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, fmt::Write};
+
+use common::{OkIterator, Result};
+use itertools::Itertools;
+
+use crate::{rules::Rules, Entry};
+
+/// How a hand ended up after ranking all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranked {
+    /// position of the entry on the input
+    pub index: usize,
+    pub cards: String,
+    pub category: String,
+    /// what the wild cards were turned into, if there were any
+    pub substitution: Option<String>,
+    pub rank: u32,
+    pub bid: u32,
+    /// the bid times the rank, which can be well past an `u32` with enough hands
+    pub winnings: u64,
+}
+
+/// Rank the entries with the given rules, from the weakest hand to the strongest.
+pub fn rank_entries(entries: &[Entry], rules: &Rules) -> Result<Vec<Ranked>> {
+    let keys = entries
+        .iter()
+        .map(|entry| rules.key(&entry.cards))
+        .ok_enumerate_context(|idx| format!("hand #{}", idx + 1))
+        .ok_collect_vec()?;
+
+    entries
+        .iter()
+        .zip(keys)
+        .enumerate()
+        .sorted_by(|(_, (_, a)), (_, (_, b))| a.cmp(b))
+        .enumerate()
+        .map(
+            |(position, (index, (entry, (category, _))))| -> Result<Ranked> {
+                let rank = position as u32 + 1;
+                let substitution = rules.substitute(&entry.cards)?;
+
+                Ok(Ranked {
+                    index,
+                    cards: entry.cards.iter().collect(),
+                    category: rules.categories()[category].to_string(),
+                    substitution: substitution.map(|cards| cards.into_iter().collect()),
+                    rank,
+                    bid: entry.bid,
+                    winnings: u64::from(entry.bid) * u64::from(rank),
+                })
+            },
+        )
+        .ok_collect_vec()
+}
+
+/// One line per hand, in the order they were ranked.
+pub fn render_report(ranked: &[Ranked]) -> String {
+    let hands = ranked
+        .iter()
+        .map(|hand| match &hand.substitution {
+            Some(cards) => format!("{} as {}", hand.cards, cards),
+            None => hand.cards.clone(),
+        })
+        .collect_vec();
+    let hand_width = hands.iter().map(String::len).max().unwrap_or(0);
+    let category_width = ranked
+        .iter()
+        .map(|hand| hand.category.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut report = String::new();
+    for (hand, cards) in ranked.iter().zip(hands) {
+        writeln!(
+            report,
+            "#{:<4} {:<hand_width$}  {:<category_width$}  {} * {} = {}",
+            hand.rank, cards, hand.category, hand.bid, hand.rank, hand.winnings,
+        )
+        .unwrap();
+    }
+
+    let total: u64 = ranked.iter().map(|hand| hand.winnings).sum();
+    write!(report, "total winnings: {}", total).unwrap();
+    report
+}
+
+/// How the ranking of a hand changed between two rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankChange {
+    pub index: usize,
+    pub cards: String,
+    pub before: (u32, String),
+    pub after: (u32, String),
+}
+
+impl RankChange {
+    pub fn moved(&self) -> i64 {
+        self.after.0 as i64 - self.before.0 as i64
+    }
+}
+
+/// Compare the rankings of the same entries, following the order of `after`.
+pub fn diff(before: &[Ranked], after: &[Ranked]) -> Vec<RankChange> {
+    let before = before
+        .iter()
+        .map(|hand| (hand.index, hand))
+        .collect::<HashMap<_, _>>();
+
+    after
+        .iter()
+        .filter_map(|hand| {
+            let previous = before.get(&hand.index)?;
+            Some(RankChange {
+                index: hand.index,
+                cards: hand.cards.clone(),
+                before: (previous.rank, previous.category.clone()),
+                after: (hand.rank, hand.category.clone()),
+            })
+        })
+        .collect()
+}
+
+pub fn render_diff(changes: &[RankChange]) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            let category = match change.before.1 == change.after.1 {
+                true => change.after.1.clone(),
+                false => format!("{} -> {}", change.before.1, change.after.1),
+            };
+            format!(
+                "{} #{} -> #{} ({:+}) {}",
+                change.cards,
+                change.before.0,
+                change.after.0,
+                change.moved(),
+                category
+            )
+        })
+        .join("\n")
+}
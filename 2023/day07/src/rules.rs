@@ -0,0 +1,267 @@
+use std::{cmp::Reverse, fmt};
+
+use common::{bail, ensure, format_err, Result};
+use itertools::Itertools;
+
+/// A kind of hand, described by the size of its groups of equal cards from the biggest to the
+/// smallest, so a full house is `[3, 2]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub name: String,
+    pub groups: Vec<usize>,
+}
+
+impl Category {
+    pub fn new(name: &str, groups: &[usize]) -> Self {
+        Self {
+            name: name.to_string(),
+            groups: groups.to_vec(),
+        }
+    }
+
+    /// A category without a proper name, it's called by its groups, like `3+2`.
+    fn from_groups(groups: Vec<usize>) -> Self {
+        Self {
+            name: groups.iter().join("+"),
+            groups,
+        }
+    }
+
+    /// Whether the groups of natural cards can become this category by turning the wild cards
+    /// into whatever is needed. Both are sorted from the biggest group to the smallest.
+    fn reachable(&self, natural: &[usize]) -> bool {
+        natural.len() <= self.groups.len()
+            && natural
+                .iter()
+                .zip(&self.groups)
+                .all(|(natural, group)| natural <= group)
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Every way to split `size` cards in groups, from the biggest group to the smallest, with the
+/// strongest first.
+fn partitions(size: usize, max: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+
+    (1..=size.min(max))
+        .rev()
+        .flat_map(|first| {
+            partitions(size - first, first)
+                .into_iter()
+                .map(move |rest| {
+                    let mut groups = vec![first];
+                    groups.extend(rest);
+                    groups
+                })
+        })
+        .collect()
+}
+
+/// How hands are ranked: the cards from the weakest to the strongest, the ones that act as any
+/// other card, how many cards make a hand and the categories from the weakest to the strongest.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    order: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+    categories: Vec<Category>,
+}
+
+/// Where a hand stands: its category and the strength of each card, to break ties.
+pub type HandKey = (usize, Vec<usize>);
+
+impl Rules {
+    /// Rules without wild cards where a hand with bigger groups always wins, which is how Camel
+    /// Cards works for any hand size.
+    pub fn new(order: &str, hand_size: usize) -> Result<Self> {
+        let order = order.chars().collect_vec();
+        ensure!(!order.is_empty(), "there must be some cards");
+        ensure!(hand_size > 0, "hands must have some cards");
+        if let Some(card) = order.iter().duplicates().next() {
+            bail!("card `{}` is more than once on the order", card);
+        }
+
+        let mut categories = partitions(hand_size, hand_size)
+            .into_iter()
+            .map(Category::from_groups)
+            .collect_vec();
+        categories.reverse();
+
+        Ok(Self {
+            order,
+            wild: Vec::new(),
+            hand_size,
+            categories,
+        })
+    }
+
+    /// The rules of part 1.
+    pub fn camel_cards() -> Self {
+        Self::new("23456789TJQKA", 5)
+            .and_then(|rules| {
+                rules.with_categories(vec![
+                    Category::new("high card", &[1, 1, 1, 1, 1]),
+                    Category::new("one pair", &[2, 1, 1, 1]),
+                    Category::new("two pairs", &[2, 2, 1]),
+                    Category::new("three of a kind", &[3, 1, 1]),
+                    Category::new("full house", &[3, 2]),
+                    Category::new("four of a kind", &[4, 1]),
+                    Category::new("five of a kind", &[5]),
+                ])
+            })
+            .expect("the camel cards rules are valid")
+    }
+
+    /// The rules of part 2, `J` is now a joker and the weakest card.
+    pub fn camel_cards_with_jokers() -> Self {
+        let mut rules = Self::camel_cards();
+        rules.order = "J23456789TQKA".chars().collect();
+        rules.wild = vec!['J'];
+        rules
+    }
+
+    /// Make these cards wild, they must be on the order to know how they break ties.
+    pub fn with_wild(mut self, wild: &str) -> Result<Self> {
+        for card in wild.chars() {
+            self.strength(card)?;
+        }
+        self.wild = wild.chars().unique().collect();
+        Ok(self)
+    }
+
+    /// Replace the categories, from the weakest to the strongest.
+    pub fn with_categories(mut self, categories: Vec<Category>) -> Result<Self> {
+        for category in &categories {
+            ensure!(
+                category.groups.iter().sum::<usize>() == self.hand_size
+                    && category.groups.windows(2).all(|pair| pair[0] >= pair[1])
+                    && !category.groups.contains(&0),
+                "category `{}` isn't made of {} cards from the biggest group to the smallest",
+                category,
+                self.hand_size
+            );
+        }
+        self.categories = categories;
+        Ok(self)
+    }
+
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+
+    pub fn is_wild(&self, card: char) -> bool {
+        self.wild.contains(&card)
+    }
+
+    /// Position of the card on the order, the higher the stronger.
+    pub fn strength(&self, card: char) -> Result<usize> {
+        self.order
+            .iter()
+            .position(|&known| known == card)
+            .ok_or_else(|| format_err!("unknown card `{}`", card))
+    }
+
+    /// Index of the best category the hand can make, wild cards taking whatever value is best.
+    pub fn classify(&self, cards: &[char]) -> Result<usize> {
+        ensure!(
+            cards.len() == self.hand_size,
+            "a hand has {} cards, not {}",
+            self.hand_size,
+            cards.len()
+        );
+        for &card in cards {
+            self.strength(card)?;
+        }
+
+        let natural = cards
+            .iter()
+            .filter(|&&card| !self.is_wild(card))
+            .counts()
+            .into_values()
+            .sorted_unstable_by(|a, b| b.cmp(a))
+            .collect_vec();
+
+        self.categories
+            .iter()
+            .rposition(|category| category.reachable(&natural))
+            .ok_or_else(|| {
+                format_err!(
+                    "hand `{}` doesn't belong to any category",
+                    cards.iter().collect::<String>()
+                )
+            })
+    }
+
+    /// The hand with its wild cards replaced by the ones that make its best category, `None` when
+    /// the hand doesn't have any wild card.
+    pub fn substitute(&self, cards: &[char]) -> Result<Option<Vec<char>>> {
+        let category = &self.categories[self.classify(cards)?];
+        if !cards.iter().any(|&card| self.is_wild(card)) {
+            return Ok(None);
+        }
+
+        // the natural cards go to the biggest groups, favouring the strongest cards on ties
+        let mut natural = cards
+            .iter()
+            .filter(|&&card| !self.is_wild(card))
+            .counts()
+            .into_iter()
+            .map(|(&card, count)| Ok((count, self.strength(card)?, card)))
+            .collect::<Result<Vec<_>>>()?;
+        natural.sort_unstable_by_key(|&(count, strength, _)| Reverse((count, strength)));
+
+        // groups without natural cards take the strongest cards that aren't on the hand
+        let mut unused = self
+            .order
+            .iter()
+            .rev()
+            .filter(|&&card| !self.is_wild(card) && !cards.contains(&card));
+
+        let mut replacements = Vec::new();
+        for (idx, &size) in category.groups.iter().enumerate() {
+            let (count, card) = match natural.get(idx) {
+                Some(&(count, _, card)) => (count, card),
+                None => (
+                    0,
+                    *unused.next().ok_or_else(|| {
+                        format_err!(
+                            "there aren't enough cards to replace the wild ones of `{}`",
+                            cards.iter().collect::<String>()
+                        )
+                    })?,
+                ),
+            };
+            replacements.extend(std::iter::repeat(card).take(size - count));
+        }
+
+        let mut replacements = replacements.into_iter();
+        Ok(Some(
+            cards
+                .iter()
+                .map(|&card| match self.is_wild(card) {
+                    true => replacements.next().unwrap_or(card),
+                    false => card,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Sorting hands by this key sorts them from the weakest to the strongest.
+    pub fn key(&self, cards: &[char]) -> Result<HandKey> {
+        let category = self.classify(cards)?;
+        let strengths = cards
+            .iter()
+            .map(|&card| self.strength(card))
+            .collect::<Result<_>>()?;
+
+        Ok((category, strengths))
+    }
+}
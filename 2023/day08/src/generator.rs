@@ -0,0 +1,139 @@
+use common::Rng;
+use itertools::Itertools;
+
+/// Letters for the nodes that are neither a start nor an end.
+const MIDDLE: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// The path of a ghost: `lead` nodes from its start, then a loop of `passes` times the turns
+/// whose end node is `exit` nodes into it. The ghost is on the end after `lead + exit` steps
+/// and then every `passes * turns` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ghost {
+    pub lead: usize,
+    pub passes: usize,
+    pub exit: usize,
+}
+
+impl Ghost {
+    /// How often the ghost is back on its end node.
+    pub fn cycle(&self, turns: usize) -> usize {
+        self.passes * turns
+    }
+}
+
+/// `size` ghosts shaped like the real ones: each of them is on its end for the first time
+/// after a whole loop, so the answer is the least common multiple of the loops.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let turns = rng.range(2..300) as usize;
+    let ghosts = (0..size.max(1))
+        .map(|_| {
+            let passes = rng.range(1..80) as usize;
+            let lead = rng.range(1..turns as u64 + 1) as usize;
+            Ghost {
+                lead,
+                passes,
+                exit: passes * turns - lead,
+            }
+        })
+        .collect_vec();
+
+    generate_network(rng, turns, &ghosts)
+}
+
+/// A network where each ghost follows exactly the path it's given, the first one going from
+/// `AAA` to `ZZZ`. The turns that aren't on a path go anywhere.
+pub fn generate_network(rng: &mut Rng, turns: usize, ghosts: &[Ghost]) -> String {
+    let directions = (0..turns.max(1))
+        .map(|_| if rng.chance(0.5) { 'L' } else { 'R' })
+        .collect::<String>();
+    let turns = directions.len();
+
+    // all the names have the same width, enough for every node of every ghost
+    let middle = ghosts
+        .iter()
+        .map(|ghost| (ghost.lead + ghost.cycle(turns)).saturating_sub(2))
+        .sum::<usize>();
+    let width = 1 + [
+        2,
+        digits(middle / MIDDLE.len(), 36),
+        digits(ghosts.len(), 10),
+    ]
+    .into_iter()
+    .max()
+    .unwrap_or_default();
+
+    let mut next_middle = 0;
+    let paths = ghosts
+        .iter()
+        .enumerate()
+        .map(|(idx, ghost)| {
+            assert!(
+                ghost.lead > 0 && ghost.exit < ghost.cycle(turns),
+                "the start and the end of {:?} must be different nodes of its path",
+                ghost
+            );
+
+            let (start, end) = match idx {
+                0 => ("A".repeat(width), "Z".repeat(width)),
+                _ => (
+                    format!("{:0>1$}A", idx, width - 1),
+                    format!("{:0>1$}Z", idx, width - 1),
+                ),
+            };
+
+            (0..ghost.lead + ghost.cycle(turns))
+                .map(|position| match position {
+                    0 => start.clone(),
+                    _ if position == ghost.lead + ghost.exit => end.clone(),
+                    _ => {
+                        next_middle += 1;
+                        middle_name(next_middle - 1, width)
+                    }
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+
+    // a loop is a whole number of passes over the turns, so every node is always left with
+    // the same turn and the other side of it is never taken
+    let names = paths.iter().flatten().collect_vec();
+    let mut lines = Vec::with_capacity(names.len());
+    for (ghost, path) in ghosts.iter().zip(&paths) {
+        for (position, node) in path.iter().enumerate() {
+            let taken = path.get(position + 1).unwrap_or(&path[ghost.lead]);
+            let other = *rng.choose(&names);
+            let (left, right) = match directions.as_bytes()[position % turns] {
+                b'L' => (taken, other),
+                _ => (other, taken),
+            };
+            lines.push(format!("{} = ({}, {})", node, left, right));
+        }
+    }
+    rng.shuffle(&mut lines);
+
+    format!("{}\n\n{}\n", directions, lines.join("\n"))
+}
+
+/// The name of the n-th node that is neither a start nor an end.
+fn middle_name(n: usize, width: usize) -> String {
+    let mut name = vec![MIDDLE[n % MIDDLE.len()]];
+    let mut rest = n / MIDDLE.len();
+    for _ in 1..width {
+        name.push(DIGITS[rest % DIGITS.len()]);
+        rest /= DIGITS.len();
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// How many digits `n` has on that base.
+fn digits(n: usize, base: usize) -> usize {
+    let mut digits = 1;
+    let mut rest = n / base;
+    while rest > 0 {
+        digits += 1;
+        rest /= base;
+    }
+    digits
+}
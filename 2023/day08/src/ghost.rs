@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use common::{bail, Result};
+use num_integer::Integer;
+
+use crate::network::Passes;
+
+/// Steps at which a walker is on an end node. The ones on `prefix` happen only once, the ones
+/// on `cycle` happen again every `period` steps, forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub prefix: Vec<u64>,
+    pub cycle: Vec<u64>,
+    pub period: u64,
+}
+
+impl Schedule {
+    /// Walk from `start` a whole pass over the turns at a time, until the walker starts a pass
+    /// on a node it already started one on, from there on it will repeat itself.
+    pub fn walk(passes: &Passes, start: u32) -> Self {
+        let mut seen = vec![None; passes.nodes()];
+        let mut hits = Vec::new();
+        let mut node = start;
+        let mut pass = 0;
+
+        let cycle_start = loop {
+            if let Some(first) = seen[node as usize] {
+                break first;
+            }
+            seen[node as usize] = Some(pass);
+
+            let pass_start = pass * passes.len;
+            hits.extend(passes.hits(node).iter().map(|&hit| pass_start + hit as u64));
+            node = passes.jump(node);
+            pass += 1;
+        };
+
+        let (prefix, cycle) = hits
+            .into_iter()
+            .partition(|&hit| hit < cycle_start * passes.len);
+        Self {
+            prefix,
+            cycle,
+            period: (pass - cycle_start) * passes.len,
+        }
+    }
+
+    /// The first step at which the walker is on an end node.
+    pub fn first(&self) -> Option<u64> {
+        self.prefix.first().or(self.cycle.first()).copied()
+    }
+
+    /// Whether the walker is on an end node after this many steps.
+    pub fn hits(&self, step: u64) -> bool {
+        self.prefix.contains(&step)
+            || self
+                .cycle
+                .iter()
+                .any(|&hit| step >= hit && (step - hit) % self.period == 0)
+    }
+}
+
+/// Join `x ≡ a (mod m)` with `x ≡ b (mod n)`, `None` when no number satisfies both.
+fn combine((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let gcd = m.extended_gcd(&n);
+    if (b - a) % gcd.gcd != 0 {
+        return None;
+    }
+
+    let lcm = m / gcd.gcd * n;
+    let offset = ((b - a) / gcd.gcd * gcd.x).mod_floor(&(n / gcd.gcd));
+    Some(((a + m * offset).mod_floor(&lcm), lcm))
+}
+
+/// The first step at which every walker is on an end node at the same time.
+pub fn first_common_step(schedules: &[Schedule]) -> Result<u64> {
+    if schedules.is_empty() {
+        bail!("there are no walkers");
+    }
+
+    // a step that happens only once for some walker has to be checked against all the others
+    let once = schedules
+        .iter()
+        .flat_map(|schedule| &schedule.prefix)
+        .copied()
+        .filter(|&step| schedules.iter().all(|schedule| schedule.hits(step)))
+        .min();
+
+    // otherwise it must be on the cycle of all of them. Each hit is a congruence, joining the
+    // walkers one at a time gives the steps where all of them so far are on a hit, which repeat
+    // every lcm of their periods. Only the lowest of the hits joined matters for each residue.
+    let mut joined = HashMap::from([(0_i128, 0_i128)]);
+    let mut modulus = 1_i128;
+    for schedule in schedules {
+        let period = schedule.period as i128;
+
+        let mut next = HashMap::new();
+        for (&residue, &earliest) in &joined {
+            for &hit in &schedule.cycle {
+                let hit = hit as i128;
+                if let Some((step, _)) = combine((residue, modulus), (hit, period)) {
+                    let earliest = earliest.max(hit);
+                    next.entry(step)
+                        .and_modify(|lowest: &mut i128| *lowest = (*lowest).min(earliest))
+                        .or_insert(earliest);
+                }
+            }
+        }
+
+        joined = next;
+        modulus = modulus.lcm(&period);
+    }
+
+    let repeated = joined
+        .into_iter()
+        .filter_map(|(step, earliest)| {
+            let behind = (earliest - step).max(0);
+            u64::try_from(step + (behind + modulus - 1) / modulus * modulus).ok()
+        })
+        .min();
+
+    match once.into_iter().chain(repeated).min() {
+        Some(step) => Ok(step),
+        None => bail!("the walkers are never on an end node at the same time"),
+    }
+}
@@ -1,132 +1,325 @@
 #![feature(iter_advance_by)]
 
-use std::{collections::HashMap, fs};
+mod generator;
+mod ghost;
+mod network;
+
+use std::fs;
 
 use itertools::Itertools;
 
 use common::*;
-use regex::Regex;
+use ghost::Schedule;
+use network::Network;
 
-fn parse(raw_data: &String) -> Result<(Vec<char>, HashMap<&str, (&str, &str)>)> {
+fn parse(raw_data: &String) -> Result<Network> {
     let mut lines = raw_data.lines();
 
-    let turns = lines.next().unwrap().chars().collect_vec();
+    let turns = lines.next().context("missing turns")?;
     lines.next();
 
-    let mut directions = HashMap::<&str, (&str, &str)>::new();
-    let rg = Regex::new(r"(\w+)").unwrap();
-    for line in lines {
-        let captures = rg.find_iter(line).map(|e| e.as_str()).collect_vec();
-
-        directions.insert(captures[0], (captures[1], captures[2]));
-    }
+    let node = Pattern::new("{node} = ({left}, {right})")?;
+    let nodes = lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let captures = node.captures(line).map_err(|err| err.at_line(idx + 2))?;
+            Ok([0, 1, 2].map(|field| captures.str(field).unwrap_or_default()))
+        })
+        .ok_collect_vec()?;
 
-    Ok((turns, directions))
+    Network::new(turns, &nodes)
 }
 
-fn compute_part1(raw_data: &String) -> u32 {
-    let (turns, directions) = parse(raw_data).unwrap();
+fn compute_part1(raw_data: &String) -> Result<u64> {
+    let network = parse(raw_data)?;
 
-    let number_of_turns = turns.len();
+    let start = network.id("AAA").context("missing node `AAA`")?;
+    let passes = network.passes(|node| node == "ZZZ");
 
-    let mut current_location = "AAA";
-    let mut num_steps = 0;
-    loop {
-        let turn_to_take = turns[num_steps % number_of_turns];
-        let location = directions.get(current_location).expect("invalid location");
+    // the walk starts on `AAA`, so `ZZZ` can't be reached without moving
+    Schedule::walk(&passes, start)
+        .first()
+        .context("`ZZZ` can't be reached from `AAA`")
+}
 
-        current_location = match turn_to_take {
-            'L' => location.0,
-            'R' => location.1,
-            _ => panic!("invalid turn"),
-        };
-        num_steps += 1;
+fn compute_part2(raw_data: &String) -> Result<u64> {
+    let network = parse(raw_data)?;
 
-        if current_location == "ZZZ" {
-            break;
-        }
+    let passes = network.passes(|node| node.ends_with('Z'));
+    let schedules = network
+        .find(|node| node.ends_with('A'))
+        .into_iter()
+        .map(|start| Schedule::walk(&passes, start))
+        .collect_vec();
+
+    ghost::first_common_step(&schedules)
+}
+
+fn main() -> Result<()> {
+    // `--generate <seed> <size>` prints a random network with that many ghosts instead
+    if let Some((mut rng, size)) = generate_request(std::env::args().skip(1))? {
+        print!("{}", generator::generate(&mut rng, size));
+        return Ok(());
     }
 
-    num_steps as u32
+    let raw_data = fs::read_to_string("2023/day08/input.txt").expect("input file does not exists");
+
+    println!("Result part 1: {}", compute_part1(&raw_data)?);
+    println!("Result part 2: {}", compute_part2(&raw_data)?);
+    Ok(())
 }
 
-fn compute_part2(raw_data: &String) -> usize {
-    let (turns, directions) = parse(raw_data).unwrap();
+common::examples! {
+    steps: include_str!("../examples/1.txt") => {
+        compute_part1: 2,
+    }
+    repeated_turns: include_str!("../examples/2.txt") => {
+        compute_part1: 6,
+    }
+    ghosts: include_str!("../examples/3.txt") => {
+        compute_part2: 6,
+    }
+}
 
-    let starts = directions
-        .keys()
-        .filter(|&key| key.chars().last().unwrap() == 'A')
-        .map(|k| *k)
+#[test]
+fn join_walkers_with_many_hits() -> Result<()> {
+    // every combination of hits would be 10^12 of them, but there are only 100 residues, and
+    // all the walkers are on an end node at step 77
+    let schedules = (0..12)
+        .map(|walker| {
+            let cycle = (0..9)
+                .map(|hit| (walker * 7 + hit * 13 + 1) % 100)
+                .chain([77])
+                .sorted()
+                .dedup()
+                .collect_vec();
+            Schedule {
+                prefix: Vec::new(),
+                cycle,
+                period: 100,
+            }
+        })
         .collect_vec();
 
-    starts
-        .into_iter()
-        .map(|mut node| {
-            turns
-                .iter()
-                .cycle()
-                .positions(|&turn| {
-                    let location = directions.get(node).unwrap();
-
-                    node = match turn {
-                        'L' => location.0,
-                        'R' => location.1,
-                        _ => panic!("invalid turn"),
-                    };
-
-                    node.chars().last().unwrap() == 'Z'
-                })
-                .map(|pos| pos + 1)
-                .next()
-                .expect("infinity loop")
-        })
-        .reduce(num_integer::lcm)
-        .unwrap()
+    let first = (0..1000).find(|&step| schedules.iter().all(|schedule| schedule.hits(step)));
+    assert!(first.is_some());
+    assert_eq!(ghost::first_common_step(&schedules).ok(), first);
+    Ok(())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day08/input.txt").expect("input file does not exists");
+#[test]
+fn test_part2_without_lcm_shortcuts() -> Result<()> {
+    // 1A is on an end node at steps 2, 5, 8... and 2A on every odd step
+    const SHIFTED: &str = "L
+
+1A = (1B, 1B)
+1B = (1Z, 1Z)
+1Z = (1C, 1C)
+1C = (1B, 1B)
+2A = (2Z, 2Z)
+2Z = (2A, 2A)";
+    assert_eq!(compute_part2(&SHIFTED.to_string())?, 5);
+
+    // 3A goes through an end node only once
+    const ONCE: &str = "L
+
+2A = (2Z, 2Z)
+2Z = (2A, 2A)
+3A = (3Z, 3Z)
+3Z = (3B, 3B)
+3B = (3B, 3B)";
+    assert_eq!(compute_part2(&ONCE.to_string())?, 1);
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+    // 1A is only on odd steps and 4A only on even ones
+    const NEVER: &str = "L
+
+2A = (2Z, 2Z)
+2Z = (2A, 2A)
+4A = (4B, 4B)
+4B = (4Z, 4Z)
+4Z = (4B, 4B)";
+    assert!(compute_part2(&NEVER.to_string()).is_err());
+
+    Ok(())
 }
 
 #[test]
-fn test_part1() -> Result<()> {
-    const INPUT1: &str = "RL
-
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)";
-
-    const INPUT2: &str = "LLR
+fn walk_whole_passes() -> Result<()> {
+    const INPUT: &str = "LLR
 
 AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)";
 
-    assert_eq!(compute_part1(&INPUT1.to_string()), 2);
-    assert_eq!(compute_part1(&INPUT2.to_string()), 6);
+    let input = INPUT.to_string();
+    let network = parse(&input)?;
+    let [aaa, bbb, zzz] = ["AAA", "BBB", "ZZZ"].map(|name| network.id(name).unwrap());
+    let passes = network.passes(|node| node == "ZZZ");
+
+    assert_eq!(network.name(bbb), "BBB");
+    assert_eq!((network.step(aaa, 0), network.step(bbb, 2)), (bbb, zzz));
+    assert_eq!((passes.jump(aaa), passes.jump(bbb)), (bbb, zzz));
+    assert_eq!(passes.hits(bbb), [] as [u32; 0]);
+    assert_eq!(passes.hits(zzz), [0, 1, 2]);
+
+    assert!(Network::new("LX", &[["AAA", "AAA", "AAA"]]).is_err());
+    assert!(Network::new("L", &[["AAA", "BBB", "AAA"]]).is_err());
+
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<()> {
-    const INPUT: &str = "LR
-
-11A = (11B, XXX)
-11B = (XXX, 11Z)
-11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)";
-
-    assert_eq!(compute_part2(&INPUT.to_string()), 6);
+fn solve_generated_networks() -> Result<()> {
+    use generator::Ghost;
+
+    let input = generate_reproducible(generator::generate, 3, 4)?;
+    compute_part1(&input)?;
+    compute_part2(&input)?;
+
+    // the ends are on steps 3, 6, 9...; 6, 12, 18...; and 12, 21, 30...
+    let ghosts = [
+        Ghost {
+            lead: 2,
+            passes: 1,
+            exit: 1,
+        },
+        Ghost {
+            lead: 1,
+            passes: 2,
+            exit: 5,
+        },
+        Ghost {
+            lead: 4,
+            passes: 3,
+            exit: 8,
+        },
+    ];
+    let input = generator::generate_network(&mut Rng::new(3), 3, &ghosts);
+    assert_eq!(parse(&input)?.len(), 5 + 7 + 13);
+    assert_eq!(compute_part1(&input)?, 3);
+    assert_eq!(compute_part2(&input)?, 12);
+    Ok(())
+}
+
+/// Moves all the ghosts one step at a time until they are all on an end node, `None` when
+/// they are all back where they already were on the same turn.
+#[cfg(test)]
+fn brute_force(raw_data: &String) -> Option<u64> {
+    use std::collections::HashSet;
+
+    let network = parse(raw_data).ok()?;
+    let turns = raw_data.lines().next()?.len();
+    let mut ghosts = network.find(|node| node.ends_with('A'));
+
+    let mut seen = HashSet::new();
+    for step in 0_u64.. {
+        if ghosts
+            .iter()
+            .all(|&ghost| network.name(ghost).ends_with('Z'))
+        {
+            return Some(step);
+        }
+
+        let turn = step as usize % turns;
+        if !seen.insert((turn, ghosts.clone())) {
+            return None;
+        }
+        ghosts = ghosts
+            .iter()
+            .map(|&ghost| network.step(ghost, turn))
+            .collect();
+    }
+
+    None
+}
+
+#[test]
+fn test_part2_differential() -> Result<()> {
+    use generator::Ghost;
+
+    type Input = (u64, usize, Vec<Ghost>);
+
+    let generate = |rng: &mut Rng, size: usize| -> Input {
+        let turns = rng.range(1..5) as usize;
+        let ghosts = (0..rng.range(1..4))
+            .map(|_| {
+                let passes = rng.range(1..4) as usize;
+                Ghost {
+                    lead: rng.range(1..size as u64 / 10 + 3) as usize,
+                    passes,
+                    exit: rng.index(passes * turns),
+                }
+            })
+            .collect();
+        (rng.next_u64(), turns, ghosts)
+    };
+
+    // fewer ghosts, fewer turns or shorter paths, as long as the ends are still on them
+    let shrink = |input: &Input| {
+        let (seed, turns, ghosts) = input;
+        let mut smaller = shrink_vec(ghosts)
+            .into_iter()
+            .filter(|ghosts| !ghosts.is_empty())
+            .map(|ghosts| (*seed, *turns, ghosts))
+            .collect_vec();
+        smaller.extend(
+            shrink_number(*turns as u64)
+                .into_iter()
+                .map(|turns| (*seed, turns as usize, ghosts.clone())),
+        );
+        for (idx, ghost) in ghosts.iter().enumerate() {
+            let shorter = [
+                Ghost {
+                    lead: ghost.lead - 1,
+                    ..*ghost
+                },
+                Ghost {
+                    passes: ghost.passes - 1,
+                    ..*ghost
+                },
+                Ghost {
+                    exit: ghost.exit.saturating_sub(1),
+                    ..*ghost
+                },
+            ];
+            smaller.extend(shorter.into_iter().map(|ghost| {
+                let mut ghosts = ghosts.clone();
+                ghosts[idx] = ghost;
+                (*seed, *turns, ghosts)
+            }));
+        }
+
+        smaller.retain(|(_, turns, ghosts)| {
+            *turns > 0
+                && ghosts
+                    .iter()
+                    .all(|ghost| ghost.lead > 0 && ghost.exit < ghost.cycle(*turns))
+        });
+        smaller.retain(|shrunk| shrunk != input);
+        smaller
+    };
+
+    let network = |(seed, turns, ghosts): &Input| {
+        generator::generate_network(&mut Rng::new(*seed), *turns, ghosts)
+    };
+    differential(
+        200,
+        generate,
+        shrink,
+        |input| compute_part2(&network(input)).ok(),
+        |input| brute_force(&network(input)),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn fuzz_networks() -> Result<()> {
+    let corpus = [
+        include_str!("../examples/1.txt"),
+        include_str!("../examples/2.txt"),
+        include_str!("../examples/3.txt"),
+    ];
+
+    common::fuzz_text(&corpus, 20_000, &[compute_part1, compute_part2])?;
     Ok(())
 }
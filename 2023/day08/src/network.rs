@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use common::{bail, format_err, Result};
+
+/// The map with the nodes interned into dense ids, so walking it is only indexing vectors.
+#[derive(Debug, Clone)]
+pub struct Network<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, u32>,
+    /// the left and the right node of each node
+    adjacency: Vec<[u32; 2]>,
+    /// 0 to go left and 1 to go right
+    turns: Vec<usize>,
+}
+
+impl<'a> Network<'a> {
+    /// Build the network from the turns and the `(node, left, right)` of every node, all the
+    /// nodes that are pointed to must be defined.
+    pub fn new(turns: &str, nodes: &[[&'a str; 3]]) -> Result<Self> {
+        let turns = turns
+            .chars()
+            .map(|turn| match turn {
+                'L' => Ok(0),
+                'R' => Ok(1),
+                turn => bail!("invalid turn `{}`", turn),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if turns.is_empty() {
+            bail!("there are no turns to take");
+        }
+
+        let names = nodes.iter().map(|[node, ..]| *node).collect::<Vec<_>>();
+        let mut ids = HashMap::with_capacity(names.len());
+        for (id, &name) in names.iter().enumerate() {
+            if ids.insert(name, id as u32).is_some() {
+                bail!("node `{}` is defined more than once", name);
+            }
+        }
+
+        let adjacency = nodes
+            .iter()
+            .map(|&[node, left, right]| {
+                let id = |name: &str| {
+                    ids.get(name).copied().ok_or_else(|| {
+                        format_err!("node `{}` goes to `{}`, which doesn't exist", node, name)
+                    })
+                };
+                Ok([id(left)?, id(right)?])
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            names,
+            ids,
+            adjacency,
+            turns,
+        })
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> &'a str {
+        self.names[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Ids of the nodes whose name matches the predicate.
+    pub fn find(&self, predicate: impl Fn(&str) -> bool) -> Vec<u32> {
+        (0..self.len() as u32)
+            .filter(|&id| predicate(self.name(id)))
+            .collect()
+    }
+
+    /// Where a node goes when taking the turn at that index.
+    #[inline]
+    pub fn step(&self, node: u32, turn: usize) -> u32 {
+        self.adjacency[node as usize][self.turns[turn]]
+    }
+
+    /// Precompute for every node where a whole pass over the turns ends, and when it goes
+    /// through the nodes matching `is_end` on the way.
+    pub fn passes(&self, is_end: impl Fn(&str) -> bool) -> Passes {
+        let ends = (0..self.len() as u32)
+            .map(|id| is_end(self.name(id)))
+            .collect::<Vec<_>>();
+
+        let (jumps, hits) = (0..self.len() as u32)
+            .map(|start| {
+                let mut node = start;
+                let mut hits = Vec::new();
+                for turn in 0..self.turns.len() {
+                    if ends[node as usize] {
+                        hits.push(turn as u32);
+                    }
+                    node = self.step(node, turn);
+                }
+                (node, hits)
+            })
+            .unzip();
+
+        Passes {
+            len: self.turns.len() as u64,
+            jumps,
+            hits,
+        }
+    }
+}
+
+/// Jump tables to walk a whole pass over the turns at once, see [`Network::passes`].
+#[derive(Debug, Clone)]
+pub struct Passes {
+    /// steps on a pass, the number of turns
+    pub len: u64,
+    jumps: Vec<u32>,
+    hits: Vec<Vec<u32>>,
+}
+
+impl Passes {
+    pub fn nodes(&self) -> usize {
+        self.jumps.len()
+    }
+
+    /// The node at the end of a pass starting on `node`.
+    #[inline]
+    pub fn jump(&self, node: u32) -> u32 {
+        self.jumps[node as usize]
+    }
+
+    /// Steps into a pass starting on `node` at which the walker is on an end node.
+    #[inline]
+    pub fn hits(&self, node: u32) -> &[u32] {
+        &self.hits[node as usize]
+    }
+}
@@ -1,89 +1,135 @@
 #![feature(iter_advance_by)]
 
-use std::{collections::HashMap, fs};
+mod polynomial;
 
-use itertools::Itertools;
+use std::io::BufRead;
 
 use common::*;
-use regex::Regex;
-
-fn parse(raw_data: &String) -> Result<Vec<Vec<i32>>> {
-    let data = raw_data
-        .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|p| p.parse::<i32>().unwrap())
-                .collect_vec()
-        })
-        .collect_vec();
-
-    Ok(data)
-}
+use polynomial::Polynomial;
 
-fn solve(sequence: &Vec<i32>) -> i32 {
-    if sequence.iter().all(|&v| v == 0) {
-        return 0;
-    }
+fn values(line: &str) -> Result<Vec<i128>> {
+    numbers(line).ok_collect_vec()
+}
 
-    let mut next_vec = Vec::with_capacity(sequence.len());
-    for i in 1..sequence.len() {
-        next_vec.push(sequence[i] - sequence[i - 1]);
-    }
+/// Add up the values some steps before (negative) or after (positive) every sequence, the
+/// sequences that run out of values are extrapolated as if the next differences were zeros.
+fn extrapolate_all(lines: LineSource<impl BufRead>, steps: i64) -> Result<i128> {
+    lines.fold_lines(0_i128, |sum, line| {
+        let polynomial = Polynomial::fit_padded(&values(line)?)?;
+        let value = match steps < 0 {
+            true => polynomial.backward(steps.unsigned_abs()),
+            false => polynomial.forward(steps as u64),
+        }?;
+        sum.checked_add(value)
+            .context("the sum doesn't fit in 128 bits")
+    })
+}
 
-    sequence.last().unwrap() + solve(&next_vec)
+#[cfg(test)]
+fn compute_part1(raw_data: &str) -> Result<i128> {
+    extrapolate_all(LineSource::new(raw_data.as_bytes()), 1)
 }
 
-fn solve_prev(sequence: &Vec<i32>) -> i32 {
-    if sequence.iter().all(|&v| v == 0) {
-        return 0;
-    }
+#[cfg(test)]
+fn compute_part2(raw_data: &str) -> Result<i128> {
+    extrapolate_all(LineSource::new(raw_data.as_bytes()), -1)
+}
 
-    let mut next_vec = Vec::with_capacity(sequence.len());
-    for i in 1..sequence.len() {
-        next_vec.push(sequence[i] - sequence[i - 1]);
+fn main() -> Result<()> {
+    const INPUT: &str = "2023/day09/input.txt";
+
+    // the input is read as it goes, so it can be as big as needed
+    println!(
+        "Result part 1: {}",
+        extrapolate_all(LineSource::open(INPUT)?, 1)?
+    );
+    println!(
+        "Result part 2: {}",
+        extrapolate_all(LineSource::open(INPUT)?, -1)?
+    );
+
+    // how far to look can be given too, like `-3` for three steps before the first values
+    if let Some(steps) = std::env::args().nth(1) {
+        let steps = steps.parse()?;
+        println!(
+            "Result {} steps away: {}",
+            steps,
+            extrapolate_all(LineSource::open(INPUT)?, steps)?
+        );
+
+        // only the sequences that really are polynomials have a degree
+        let highest = LineSource::open(INPUT)?.fold_lines(0, |highest, line| {
+            Ok(Polynomial::fit(&values(line)?)?.degree().max(highest))
+        })?;
+        println!("Highest degree: {}", highest);
     }
 
-    sequence.first().unwrap() - solve_prev(&next_vec)
+    Ok(())
 }
 
-fn compute_part1(raw_data: &String) -> i32 {
-    let data = parse(raw_data).unwrap();
-
-    data.iter().map(solve).sum()
+common::examples! {
+    histories: include_str!("../examples/1.txt") => {
+        compute_part1: 114,
+        compute_part2: 2,
+    }
 }
 
-fn compute_part2(raw_data: &String) -> i32 {
-    let data = parse(raw_data).unwrap();
+#[test]
+fn test_part1_long_history() -> Result<()> {
+    const INPUT: &str = "-4 -4 9 49 141 337 751 1623 3426 7039 14036 27210 51589 96428 178991 331412 612714 1129686 2074056 3792092 6922209";
+    assert_eq!(compute_part1(INPUT)?, 12677569);
 
-    data.iter().map(solve_prev).sum()
+    Ok(())
 }
 
-fn main() {
-    let raw_data = fs::read_to_string("2023/day09/input.txt").expect("input file does not exists");
+#[test]
+fn fit_polynomials() -> Result<()> {
+    let polynomial = Polynomial::fit(&[10, 13, 16, 21, 30, 45])?;
+    assert_eq!(polynomial.degree(), 3);
+    assert_eq!(polynomial.forward(1)?, 68);
+    assert_eq!(polynomial.backward(1)?, 5);
+    assert_eq!(polynomial.at(2)?, 16);
+
+    // any number of steps, values of `x³ - 2x` from `x = 3`
+    let cube = |x: i128| x * x * x - 2 * x;
+    let polynomial = Polynomial::fit(&(3..10).map(cube).collect::<Vec<_>>())?;
+    assert_eq!(polynomial.forward(100)?, cube(109));
+    assert_eq!(polynomial.backward(20)?, cube(-17));
+
+    // too big for `i32` or even `i64`
+    let huge = (1..=6).map(|x| cube(x * 10_000_000)).collect::<Vec<_>>();
+    assert_eq!(Polynomial::fit(&huge)?.forward(1)?, cube(70_000_000));
+
+    assert_eq!(Polynomial::fit(&[0, 0])?.degree(), 0);
+    assert_eq!(Polynomial::fit(&[7, 7])?.forward(3)?, 7);
+    assert!(Polynomial::fit(&[7]).is_err());
+    assert!(Polynomial::fit(&[1, 2, 4, 8]).is_err());
+    assert!(Polynomial::fit(&[1, 2]).is_err());
+    assert!(Polynomial::fit(&[]).is_err());
+    assert!(Polynomial::fit_padded(&[]).is_err());
+    assert!(Polynomial::fit(&[i128::MAX - 2, i128::MAX - 1, i128::MAX])?
+        .forward(1)
+        .is_err());
 
-    println!("Result part 1: {}", compute_part1(&raw_data));
-    println!("Result part 2: {}", compute_part2(&raw_data));
+    Ok(())
 }
 
 #[test]
-fn test_part1() -> Result<()> {
-    const INPUT1: &str = "0 3 6 9 12 15
-    1 3 6 10 15 21
-    10 13 16 21 30 45";
-    assert_eq!(compute_part1(&INPUT1.to_string()), 114);
-
-    const INPUT2: &str = "-4 -4 9 49 141 337 751 1623 3426 7039 14036 27210 51589 96428 178991 331412 612714 1129686 2074056 3792092 6922209";
-    assert_eq!(compute_part1(&INPUT2.to_string()), 12677569);
+fn extrapolate_short_histories() -> Result<()> {
+    // the differences past the last row are taken as zeros
+    assert_eq!(compute_part1("1 2")?, 3);
+    assert_eq!(compute_part2("1 2")?, 0);
+    assert_eq!(compute_part1("7")?, 7);
+    assert_eq!(compute_part1("1 2 4 8")?, 15);
+    assert_eq!(Polynomial::fit_padded(&[1, 2, 4, 8])?.degree(), 3);
 
     Ok(())
 }
 
 #[test]
-fn test_part2() -> Result<()> {
-    const INPUT: &str = "0 3 6 9 12 15
-    1 3 6 10 15 21
-    10 13 16 21 30 45";
+fn fuzz_histories() -> Result<()> {
+    let corpus = [include_str!("../examples/1.txt")];
 
-    assert_eq!(compute_part2(&INPUT.to_string()), 2);
+    common::fuzz_text(&corpus, 20_000, &[compute_part1, compute_part2])?;
     Ok(())
 }
@@ -0,0 +1,97 @@
+use common::{ensure, Context, Result};
+
+/// The polynomial of lowest degree going through a sequence of values, taken at `x = 0, 1, ...`
+///
+/// It is kept in Newton's forward difference form, `p(x) = Σ Δᵏp(0) · C(x, k)`, which only
+/// needs integers to be evaluated anywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// first value of each row of differences, `Δᵏp(0)`
+    differences: Vec<i128>,
+    /// number of values it was fitted to
+    len: usize,
+}
+
+impl Polynomial {
+    /// Find the polynomial by differentiating the sequence until all the differences are zero.
+    /// It's an error when that doesn't happen while there are still values left, because any
+    /// sequence fits a polynomial of its length minus one so there is nothing to extrapolate.
+    pub fn fit(values: &[i128]) -> Result<Self> {
+        Self::differentiate(values, false)
+    }
+
+    /// Same as [`Polynomial::fit`], but when the values run out before the differences are all
+    /// zero the missing ones are taken as zeros, which is what the puzzle does. A sequence like
+    /// `1 2 4 8` then becomes the cubic through its four values.
+    pub fn fit_padded(values: &[i128]) -> Result<Self> {
+        Self::differentiate(values, true)
+    }
+
+    fn differentiate(values: &[i128], padded: bool) -> Result<Self> {
+        ensure!(!values.is_empty(), "there are no values to fit");
+
+        let mut differences = Vec::new();
+        let mut row = values.to_vec();
+        while row.iter().any(|&value| value != 0) {
+            ensure!(
+                padded || row.len() > 1,
+                "{} values aren't enough to tell the degree of the sequence",
+                values.len()
+            );
+
+            differences.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|pair| pair[1].checked_sub(pair[0]))
+                .collect::<Option<_>>()
+                .context("the differences don't fit in 128 bits")?;
+        }
+
+        Ok(Self {
+            differences,
+            len: values.len(),
+        })
+    }
+
+    /// Degree of the polynomial, a sequence of zeros has degree 0.
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    /// Evaluate it at any `x`, even a negative one, failing when it overflows.
+    pub fn at(&self, x: i128) -> Result<i128> {
+        let mut result = 0_i128;
+        // C(x, k), updated with C(x, k + 1) = C(x, k) · (x - k) / (k + 1), which is exact
+        let mut binomial = 1_i128;
+
+        let overflow = || format!("p({}) doesn't fit in 128 bits", x);
+
+        for (k, &difference) in self.differences.iter().enumerate() {
+            result = difference
+                .checked_mul(binomial)
+                .and_then(|term| result.checked_add(term))
+                .with_context(overflow)?;
+
+            if k + 1 == self.differences.len() {
+                break;
+            }
+            let k = k as i128;
+            binomial = binomial
+                .checked_mul(x - k)
+                .map(|product| product / (k + 1))
+                .with_context(overflow)?;
+        }
+
+        Ok(result)
+    }
+
+    /// The value `steps` after the last one of the sequence.
+    pub fn forward(&self, steps: u64) -> Result<i128> {
+        self.at(self.len as i128 - 1 + steps as i128)
+    }
+
+    /// The value `steps` before the first one of the sequence.
+    pub fn backward(&self, steps: u64) -> Result<i128> {
+        self.at(-(steps as i128))
+    }
+}
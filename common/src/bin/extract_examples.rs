@@ -0,0 +1,37 @@
+//! Saves the examples of a puzzle description on the `examples` directory of its day, and
+//! prints the [`common::examples!`] that checks the solutions against them.
+//!
+//! Save the puzzle page (or its markdown) as `puzzle.html` (or `puzzle.md`) on the day
+//! directory, then `cargo run -p common --bin extract_examples -- 2023/day06`.
+
+use std::{fs, path::Path};
+
+use common::{bail, declare_examples, ensure, extract_examples, Context, Result};
+
+fn main() -> Result<()> {
+    let Some(day) = std::env::args().nth(1) else {
+        bail!("usage: extract_examples <day directory>");
+    };
+    let day = Path::new(&day);
+
+    let description = ["puzzle.html", "puzzle.md"]
+        .iter()
+        .map(|name| day.join(name))
+        .find(|path| path.exists())
+        .with_context(|| format!("no puzzle.html or puzzle.md on {}", day.display()))?;
+    let examples = extract_examples(&fs::read_to_string(&description)?);
+    ensure!(
+        !examples.is_empty(),
+        "no examples with an answer on {}",
+        description.display()
+    );
+
+    let directory = day.join("examples");
+    fs::create_dir_all(&directory)?;
+    for (idx, example) in examples.iter().enumerate() {
+        fs::write(directory.join(format!("{}.txt", idx + 1)), &example.input)?;
+    }
+    print!("{}", declare_examples(&examples));
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use anyhow::{format_err, Result};
+
+/// A group of consecutive non-blank lines, see [`blocks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    text: &'a str,
+    offset: usize,
+    line_index: usize,
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Block<'a> {
+    /// Name of the block when its first line ends with a `:`, like `"seed-to-soil map"` for a
+    /// `"seed-to-soil map:"` line.
+    pub fn header(&self) -> Option<&'a str> {
+        self.lines.first().and_then(|line| line.strip_suffix(':'))
+    }
+
+    /// The lines after the header, or all of them when the block doesn't have one.
+    pub fn body(&self) -> &[&'a str] {
+        match self.header() {
+            Some(_) => &self.lines[1..],
+            None => &self.lines,
+        }
+    }
+
+    /// All the lines of the block, without trailing whitespace.
+    pub fn lines(&self) -> &[&'a str] {
+        &self.lines
+    }
+
+    /// Whitespace separated words of all the lines.
+    pub fn words(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.lines.iter().flat_map(|line| line.split_whitespace())
+    }
+
+    /// The block as it is on the input, from the start of its first line to the end of the last.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Byte offset of the block on the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// 0-based number of the first line of the block on the input.
+    pub fn line_index(&self) -> usize {
+        self.line_index
+    }
+}
+
+/// Iterator over the blocks of an input, see [`blocks`].
+#[derive(Debug, Clone)]
+pub struct Blocks<'a> {
+    input: &'a str,
+    offset: usize,
+    line_index: usize,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = Block<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut block: Option<Block<'a>> = None;
+
+        while self.offset < self.input.len() {
+            let rest = &self.input[self.offset..];
+            let line_len = rest.find('\n').map_or(rest.len(), |pos| pos + 1);
+            let line = rest[..line_len].trim_end();
+
+            if line.is_empty() {
+                if block.is_some() {
+                    break;
+                }
+            } else {
+                let block = block.get_or_insert_with(|| Block {
+                    text: "",
+                    offset: self.offset,
+                    line_index: self.line_index,
+                    lines: Vec::new(),
+                });
+                block.lines.push(line);
+                block.text = &self.input[block.offset..self.offset + line.len()];
+            }
+
+            self.offset += line_len;
+            self.line_index += 1;
+        }
+
+        block
+    }
+}
+
+/// Split an input into blocks of lines separated by blank lines.
+///
+/// Works with `\n` and `\r\n` line endings, lines with only whitespace are taken as blank and
+/// several blank lines in a row are the same as one.
+pub fn blocks(input: &str) -> Blocks<'_> {
+    Blocks {
+        input,
+        offset: 0,
+        line_index: 0,
+    }
+}
+
+/// An input made of blocks that can be found by their header, like the almanac:
+///
+/// ```text
+/// seeds: 79 14 55 13
+///
+/// seed-to-soil map:
+/// 50 98 2
+/// 52 50 48
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sections<'a> {
+    blocks: Vec<Block<'a>>,
+}
+
+impl<'a> Sections<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            blocks: blocks(input).collect(),
+        }
+    }
+
+    /// All the blocks, in the same order as on the input.
+    pub fn blocks(&self) -> &[Block<'a>] {
+        &self.blocks
+    }
+
+    /// Blocks that have a header.
+    pub fn named(&self) -> impl Iterator<Item = (&'a str, &Block<'a>)> {
+        self.blocks
+            .iter()
+            .filter_map(|block| block.header().map(|name| (name, block)))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Block<'a>> {
+        self.blocks
+            .iter()
+            .find(|block| block.header() == Some(name))
+    }
+
+    /// Same as [`Sections::get`] but it's an error when the section doesn't exist.
+    pub fn section(&self, name: &str) -> Result<&Block<'a>> {
+        self.get(name)
+            .ok_or_else(|| format_err!("missing section `{}`", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn split_on_blank_lines() {
+        let input = "a b\r\nc\r\n  \r\n\r\nd:\n1 2 \n\n\n";
+        let blocks = blocks(input).collect::<Vec<_>>();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].lines(), ["a b", "c"]);
+        assert_eq!(blocks[0].words().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(blocks[0].text(), "a b\r\nc");
+        assert_eq!(blocks[0].header(), None);
+        assert_eq!(blocks[1].header(), Some("d"));
+        assert_eq!(blocks[1].body(), ["1 2"]);
+        assert_eq!((blocks[1].offset(), blocks[1].line_index()), (14, 4));
+    }
+
+    #[test]
+    fn find_sections_by_name() -> Result<()> {
+        let sections = Sections::new("seeds: 1 2\n\nb-to-c map:\n3\n\na-to-b map:\n4\n5");
+
+        assert_eq!(sections.section("a-to-b map")?.body(), ["4", "5"]);
+        assert_eq!(sections.section("b-to-c map")?.body(), ["3"]);
+        assert_eq!(sections.blocks()[0].lines(), ["seeds: 1 2"]);
+        assert_eq!(sections.named().count(), 2);
+        assert!(sections.section("c-to-d map").is_err());
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn blocks_round_trip(
+            expected in prop::collection::vec(
+                prop::collection::vec(" ?[a-z:]{1,8}( [a-z0-9]{1,4})?", 1..4),
+                0..5,
+            ),
+            blank in prop::sample::select(vec!["", "  ", "\t"]),
+            crlf in any::<bool>(),
+            leading in 0_usize..3,
+        ) {
+            let newline = if crlf { "\r\n" } else { "\n" };
+            let separator = format!("{}{}{}", newline, blank, newline);
+            let input = format!(
+                "{}{}{}",
+                format!("{}{}", blank, newline).repeat(leading),
+                expected
+                    .iter()
+                    .map(|lines| lines.join(newline))
+                    .collect::<Vec<_>>()
+                    .join(&separator),
+                newline,
+            );
+
+            let found = blocks(&input).collect::<Vec<_>>();
+            prop_assert_eq!(found.iter().map(|block| block.lines().to_vec()).collect::<Vec<_>>(), expected);
+
+            for block in found {
+                prop_assert!(input[block.offset()..].starts_with(block.text()));
+                prop_assert_eq!(input.lines().nth(block.line_index()), Some(block.lines()[0]));
+                prop_assert_eq!(block.text().lines().count(), block.lines().len());
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{fmt, ops::Range};
+
+use crate::ParseError;
+
+/// A parse error pinned to its place on the input, rendered like a compiler error:
+///
+/// ```text
+/// expected field `letter`
+///  --> input.txt:5:5
+///   |
+/// 5 | 1-4 m abc
+///   |     ^^^^^
+/// ```
+///
+/// It implements `std::error::Error`, so it can be returned with `?` from functions returning
+/// an `anyhow::Result` and wrapped with `anyhow::Context` like any other error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// file the input was read from, if known
+    pub file: Option<String>,
+    /// 1-based line number
+    pub line: usize,
+    /// byte range within the line
+    pub span: Range<usize>,
+    /// what we were expecting to find there
+    pub expected: String,
+    /// the whole offending line
+    pub source_line: String,
+}
+
+impl Diagnostic {
+    /// Build a diagnostic for a span of a single line, `index` is the 0-based line number.
+    /// A span that cuts a character in two is widened to cover all of it.
+    pub fn on_line(
+        index: usize,
+        source_line: &str,
+        span: Range<usize>,
+        expected: impl Into<String>,
+    ) -> Self {
+        let mut end = span.end.min(source_line.len());
+        while !source_line.is_char_boundary(end) {
+            end += 1;
+        }
+        let mut start = span.start.min(end);
+        while !source_line.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        Self {
+            file: None,
+            line: index + 1,
+            span: start..end,
+            expected: expected.into(),
+            source_line: source_line.to_string(),
+        }
+    }
+
+    /// Build a diagnostic from a span over a whole (multi-line) input, the line is found from
+    /// the start of the span.
+    pub fn in_source(source: &str, span: Range<usize>, expected: impl Into<String>) -> Self {
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |pos| start + pos);
+        let index = source[..line_start].matches('\n').count();
+
+        Self::on_line(
+            index,
+            source[line_start..line_end].trim_end_matches('\r'),
+            start - line_start..span.end.min(line_end) - line_start,
+            expected,
+        )
+    }
+
+    /// Record the file the input came from.
+    pub fn in_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// 1-based column where the span starts, counted in characters.
+    pub fn column(&self) -> usize {
+        self.source_line[..self.span.start].chars().count() + 1
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let padding = self.source_line[..self.span.start].chars().count();
+        let width = self.source_line[self.span.clone()].chars().count().max(1);
+
+        writeln!(f, "expected {}", self.expected)?;
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter,
+            self.file.as_deref().unwrap_or("<input>"),
+            self.line,
+            self.column()
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, self.source_line)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(padding),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Attach the source text to a [`ParseError`], turning it into a [`Diagnostic`].
+pub trait Diagnose<T> {
+    /// The error comes from parsing the given line, `index` is its 0-based number.
+    fn on_line(self, index: usize, line: &str) -> Result<T, Diagnostic>;
+
+    /// The error comes from parsing the slice of `source` that starts at `offset`.
+    fn in_source(self, source: &str, offset: usize) -> Result<T, Diagnostic>;
+}
+
+impl<T> Diagnose<T> for Result<T, ParseError> {
+    fn on_line(self, index: usize, line: &str) -> Result<T, Diagnostic> {
+        self.map_err(|err| Diagnostic::on_line(index, line, err.span, err.expected))
+    }
+
+    fn in_source(self, source: &str, offset: usize) -> Result<T, Diagnostic> {
+        self.map_err(|err| {
+            let err = err.offset(offset);
+            Diagnostic::in_source(source, err.span, err.expected)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Pattern};
+
+    #[test]
+    fn render_caret_under_the_span() {
+        let pattern = Pattern::new("{min}-{max} {letter}: {password}").unwrap();
+        let diagnostic = pattern
+            .captures("1-4 m abc")
+            .on_line(4, "1-4 m abc")
+            .unwrap_err()
+            .in_file("input.txt");
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "expected field `letter`
+ --> input.txt:5:5
+  |
+5 | 1-4 m abc
+  |     ^^^^^"
+        );
+    }
+
+    #[test]
+    fn locate_the_line_on_multiline_sources() {
+        let source = "ecl:gry pid:860033327\nbyr:1937 foo:bar\r\nhgt:183cm";
+        let diagnostic = Diagnostic::in_source(source, 31..34, "a known property");
+
+        assert_eq!(diagnostic.line, 2);
+        assert_eq!(diagnostic.column(), 10);
+        assert_eq!(diagnostic.source_line, "byr:1937 foo:bar");
+        assert!(diagnostic
+            .to_string()
+            .ends_with("2 | byr:1937 foo:bar\n  |          ^^^"));
+    }
+
+    #[test]
+    fn widen_spans_to_whole_characters() {
+        let diagnostic = Diagnostic::on_line(0, "12:é5", 4..6, "a digit");
+
+        assert_eq!(diagnostic.span, 3..6);
+        assert!(diagnostic.to_string().ends_with("1 | 12:é5\n  |    ^^"));
+    }
+
+    #[test]
+    fn works_with_anyhow_context() {
+        let result = Err::<(), _>(Diagnostic::on_line(0, "x", 0..1, "a digit"));
+
+        let err = result.context("parsing the input").unwrap_err();
+        assert_eq!(err.to_string(), "parsing the input");
+        assert!(err.root_cause().is::<Diagnostic>());
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use crate::Rng;
+
+/// Shrinking stops after this many smaller inputs that still disagree, so a `shrink` that
+/// doesn't really make inputs smaller can't loop forever.
+const MAX_SHRINKS: usize = 10_000;
+
+/// An input on which the fast and the slow implementations give different answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disagreement<I, O> {
+    /// seed of the [`Rng`] that generated the original input
+    pub seed: u64,
+    /// the smallest input found that still disagrees
+    pub input: I,
+    pub fast: O,
+    pub slow: O,
+}
+
+impl<I: fmt::Debug, O: fmt::Debug> fmt::Display for Disagreement<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} gives {:?} but the reference gives {:?} (shrunk from seed {})",
+            self.input, self.fast, self.slow, self.seed
+        )
+    }
+}
+
+impl<I: fmt::Debug, O: fmt::Debug> std::error::Error for Disagreement<I, O> {}
+
+/// Runs a solution and a slow reference implementation on `cases` generated inputs, the n-th
+/// one being generated with seed `n` and size `n`. The first input where they disagree is
+/// shrunk, trying the smaller inputs from `shrink` for as long as one of them still disagrees.
+pub fn differential<I, O>(
+    cases: u64,
+    generate: impl Fn(&mut Rng, usize) -> I,
+    shrink: impl Fn(&I) -> Vec<I>,
+    fast: impl Fn(&I) -> O,
+    slow: impl Fn(&I) -> O,
+) -> Result<(), Disagreement<I, O>>
+where
+    O: PartialEq,
+{
+    let disagree = |input: &I| {
+        let (fast, slow) = (fast(input), slow(input));
+        (fast != slow).then_some((fast, slow))
+    };
+
+    for seed in 0..cases {
+        let input = generate(&mut Rng::new(seed), seed as usize);
+        let Some(mut answers) = disagree(&input) else {
+            continue;
+        };
+
+        let mut input = input;
+        for _ in 0..MAX_SHRINKS {
+            let Some((smaller, smaller_answers)) = shrink(&input)
+                .into_iter()
+                .find_map(|smaller| disagree(&smaller).map(|answers| (smaller, answers)))
+            else {
+                break;
+            };
+            input = smaller;
+            answers = smaller_answers;
+        }
+
+        let (fast, slow) = answers;
+        return Err(Disagreement {
+            seed,
+            input,
+            fast,
+            slow,
+        });
+    }
+
+    Ok(())
+}
+
+/// Smaller versions of a list: without each half, then without each of its items.
+pub fn shrink_vec<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    let half = items.len() / 2;
+    let mut smaller = Vec::new();
+    if half > 0 {
+        smaller.push(items[half..].to_vec());
+        smaller.push(items[..half].to_vec());
+    }
+    for idx in 0..items.len() {
+        let mut without = items.to_vec();
+        without.remove(idx);
+        smaller.push(without);
+    }
+    smaller
+}
+
+/// Smaller numbers to try instead of `n`, from the smallest: 0, half of it and one less.
+pub fn shrink_number(n: u64) -> Vec<u64> {
+    let mut smaller = vec![0, n / 2, n.saturating_sub(1)];
+    smaller.dedup();
+    smaller.retain(|&smaller| smaller < n);
+    smaller
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::{any, prop, prop_assert, proptest};
+
+    use super::*;
+
+    #[test]
+    fn shrink_to_a_small_counterexample() {
+        // a "fast" sum that forgets about the numbers above 100
+        let generate = |rng: &mut Rng, size| (0..size).map(|_| rng.below(200)).collect::<Vec<_>>();
+        let fast = |numbers: &Vec<u64>| numbers.iter().filter(|&&n| n <= 100).sum::<u64>();
+        let slow = |numbers: &Vec<u64>| numbers.iter().sum::<u64>();
+        let shrink = |numbers: &Vec<u64>| {
+            let mut smaller = shrink_vec(numbers);
+            for (idx, &n) in numbers.iter().enumerate() {
+                smaller.extend(shrink_number(n).into_iter().map(|n| {
+                    let mut numbers = numbers.clone();
+                    numbers[idx] = n;
+                    numbers
+                }));
+            }
+            smaller
+        };
+
+        let disagreement = differential(50, generate, shrink, fast, slow).unwrap_err();
+        assert_eq!(disagreement.input, [101]);
+        assert_eq!((disagreement.fast, disagreement.slow), (0, 101));
+
+        assert!(differential(50, generate, shrink, slow, slow).is_ok());
+    }
+
+    #[test]
+    fn smaller_lists_and_numbers() {
+        assert_eq!(
+            shrink_vec(&[1, 2, 3]),
+            [vec![2, 3], vec![1], vec![2, 3], vec![1, 3], vec![1, 2]]
+        );
+        assert!(shrink_vec::<u8>(&[]).is_empty());
+        assert_eq!(shrink_number(10), [0, 5, 9]);
+        assert_eq!(shrink_number(1), [0]);
+        assert!(shrink_number(0).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn shrinking_makes_things_smaller(items in prop::collection::vec(any::<u8>(), 0..10), n in any::<u64>()) {
+            for smaller in shrink_vec(&items) {
+                prop_assert!(smaller.len() < items.len());
+                prop_assert!(smaller.iter().all(|item| items.contains(item)));
+            }
+            prop_assert!(shrink_number(n).iter().all(|&smaller| smaller < n));
+        }
+    }
+}
@@ -0,0 +1,447 @@
+use std::fmt::{self, Write};
+
+/// Heading of the second half of a puzzle description, both on the page and on markdown.
+const PART_TWO: &str = "--- Part Two ---";
+
+/// An example input from a puzzle description and the answers the description gives for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    /// answers to part 1 and part 2, when the description has them for this input
+    pub answers: [Option<String>; 2],
+}
+
+/// Find the examples of a puzzle description, either the page saved as HTML or its markdown.
+///
+/// The example of each part is the first code block of that part, or the one of part 1 when
+/// part 2 doesn't have a block of its own, and its answer is the last emphasised code of the
+/// part. That's how almost every puzzle is written, but the blocks that come later (like the
+/// ones showing the steps) are left out, so check what was found before relying on it.
+pub fn extract_examples(description: &str) -> Vec<Example> {
+    let (first, second) = match description.find(PART_TWO) {
+        Some(at) => description.split_at(at),
+        None => (description, ""),
+    };
+    let html = description.contains("<pre>") || description.contains("<article");
+
+    let mut examples: Vec<Example> = Vec::new();
+    for (part, text) in [first, second].into_iter().enumerate() {
+        let (blocks, answers) = match html {
+            true => (html_blocks(text), html_answers(text)),
+            false => markdown_code(text),
+        };
+        let Some(answer) = answers.last() else {
+            continue;
+        };
+        let Some(input) = blocks
+            .first()
+            .or_else(|| examples.last().map(|example| &example.input))
+            .cloned()
+        else {
+            continue;
+        };
+
+        match examples.iter_mut().find(|example| example.input == input) {
+            Some(example) => example.answers[part] = Some(answer.clone()),
+            None => {
+                let mut example = Example {
+                    input,
+                    ..Default::default()
+                };
+                example.answers[part] = Some(answer.clone());
+                examples.push(example);
+            }
+        }
+    }
+
+    examples
+}
+
+/// Everything between each `open` and the following `close`, with the position it starts.
+fn between<'a>(text: &'a str, open: &str, close: &str) -> Vec<(usize, &'a str)> {
+    let mut found = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = text[rest..].find(open).map(|at| rest + at + open.len()) {
+        let Some(end) = text[start..].find(close).map(|at| start + at) else {
+            break;
+        };
+        found.push((start, &text[start..end]));
+        rest = end + close.len();
+    }
+    found
+}
+
+/// The text of an HTML fragment, without the tags and with the entities AoC uses decoded.
+fn html_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn html_blocks(text: &str) -> Vec<String> {
+    between(text, "<pre><code>", "</code></pre>")
+        .into_iter()
+        .map(|(_, block)| html_text(block))
+        .collect()
+}
+
+/// `<code><em>288</em></code>`, or the other way around, in the order they show up.
+fn html_answers(text: &str) -> Vec<String> {
+    let mut answers = between(text, "<code><em>", "</em></code>");
+    answers.extend(between(text, "<em><code>", "</code></em>"));
+    answers.sort_unstable_by_key(|&(at, _)| at);
+    answers
+        .into_iter()
+        .map(|(_, answer)| html_text(answer))
+        .collect()
+}
+
+/// The fenced blocks and the emphasised code spans (`` `*288*` `` or `` *`288`* ``, with one
+/// or two `*`) of a markdown description.
+fn markdown_code(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut blocks = Vec::new();
+    let mut answers = Vec::new();
+    let mut block: Option<String> = None;
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            match block.take() {
+                Some(finished) => blocks.push(finished),
+                None => block = Some(String::new()),
+            }
+            continue;
+        }
+        if let Some(block) = &mut block {
+            block.push_str(line);
+            block.push('\n');
+            continue;
+        }
+
+        let ticks = line
+            .match_indices('`')
+            .map(|(at, _)| at)
+            .collect::<Vec<_>>();
+        for pair in ticks.chunks_exact(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let code = &line[start + 1..end];
+            let inside = code.trim_matches('*');
+            let emphasised =
+                (code.starts_with('*') && code.ends_with('*') && inside.len() + 2 <= code.len())
+                    || (line[..start].ends_with('*') && line[end + 1..].starts_with('*'));
+            if emphasised && !inside.is_empty() {
+                answers.push(inside.to_string());
+            }
+        }
+    }
+
+    (blocks, answers)
+}
+
+/// The [`examples!`](crate::examples) to paste on the day's `main.rs`, with the input of the
+/// n-th example saved on `examples/n.txt` next to its `src` directory.
+pub fn declare_examples(examples: &[Example]) -> String {
+    let mut declaration = String::from("common::examples! {\n");
+    for (idx, example) in examples.iter().enumerate() {
+        let _ = writeln!(
+            declaration,
+            "    example{0}: include_str!(\"../examples/{0}.txt\") => {{",
+            idx + 1
+        );
+        for (part, answer) in example.answers.iter().enumerate() {
+            let Some(answer) = answer else {
+                continue;
+            };
+            // numbers go as they are, anything else is a string
+            let _ = match answer.parse::<i128>() {
+                Ok(_) => writeln!(declaration, "        compute_part{}: {},", part + 1, answer),
+                Err(_) => writeln!(
+                    declaration,
+                    "        compute_part{}: {:?},",
+                    part + 1,
+                    answer
+                ),
+            };
+        }
+        declaration.push_str("    }\n");
+    }
+    declaration.push_str("}\n");
+    declaration
+}
+
+/// What a solution gives back, either the answer itself or a `Result` with it.
+pub trait Answer {
+    type Value: fmt::Debug;
+
+    /// The answer, or the error the solution failed with.
+    fn answer(self) -> Result<Self::Value, String>;
+}
+
+macro_rules! plain_answers {
+    ($($ty:ty)*) => {
+        $(
+            impl Answer for $ty {
+                type Value = $ty;
+
+                fn answer(self) -> Result<$ty, String> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+plain_answers!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize String);
+
+impl<T: Answer, E: fmt::Display> Answer for Result<T, E> {
+    type Value = T::Value;
+
+    fn answer(self) -> Result<T::Value, String> {
+        self.map_err(|err| format!("{:#}", err))?.answer()
+    }
+}
+
+/// Panics when a solution doesn't give the expected answer on an example, or fails on it,
+/// showing the answers and the input. It's what the tests generated by [`examples!`] call.
+#[track_caller]
+pub fn check_example<A, E>(example: &str, solution: &str, input: &str, answer: A, expected: E)
+where
+    A: Answer,
+    A::Value: PartialEq<E>,
+    E: fmt::Debug,
+{
+    let input = || {
+        input
+            .lines()
+            .flat_map(|line| ["    ", line, "\n"])
+            .collect::<String>()
+    };
+
+    match answer.answer() {
+        Ok(answer) if answer == expected => {}
+        Ok(answer) => panic!(
+            "{} gives the wrong answer on example `{}`\n  expected: {:?}\n     found: {:?}\n  on the input:\n{}",
+            solution,
+            example,
+            expected,
+            answer,
+            input()
+        ),
+        Err(err) => panic!(
+            "{} fails on example `{}`: {}\n  expected: {:?}\n  on the input:\n{}",
+            solution,
+            example,
+            err,
+            expected,
+            input()
+        ),
+    }
+}
+
+/// Declares examples inline, each with the solutions it's checked with and their answers:
+///
+/// ```ignore
+/// common::examples! {
+///     hands: EXAMPLE => {
+///         compute_part1: 6440,
+///         compute_part2: 5905,
+///     }
+/// }
+/// ```
+///
+/// Every example is a module with a test for each solution, like `hands::compute_part1`. The
+/// input is anything that goes into a `String::from`, and the solutions are called with a
+/// `&String`, like the `compute_partN` of each day.
+#[macro_export]
+macro_rules! examples {
+    ($($name:ident: $input:expr => { $($solve:ident: $expected:expr),+ $(,)? })+) => {
+        $(
+            #[cfg(test)]
+            mod $name {
+                #[allow(unused_imports)]
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $solve() {
+                        let input = String::from($input);
+                        $crate::check_example(
+                            stringify!($name),
+                            stringify!($solve),
+                            &input,
+                            super::$solve(&input),
+                            $expected,
+                        );
+                    }
+                )+
+            }
+        )+
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<main>
+<article class="day-desc"><h2>--- Day 6: Wait For It ---</h2><p>For example:</p>
+<pre><code>Time:      7  15   30
+Distance:  9  40  200
+</code></pre>
+<p>The first race lasts <code>7</code> milliseconds, you could win in <code><em>4</em></code> ways.</p>
+<p>If you multiply these values together, you get <code><em>288</em></code> (<code>4</code> * <code>8</code> * <code>9</code>).</p>
+</article>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>So, the example from before now has a single race with a record of <code>940200</code>.</p>
+<p>You could hold the button anywhere from <code>14</code> to <code>71516</code> and beat the record, a total of <em><code>71503</code></em> ways!</p>
+</article>
+</main>"#;
+
+    const MARKDOWN: &str = "\\--- Day 8: Haunted Wasteland ---
+----------
+
+For example:
+
+```
+RL
+
+AAA = (BBB, CCC)
+```
+
+Starting with `AAA`, you need to look up the next element, reaching `ZZZ` in `*2*` steps.
+
+\\--- Part Two ---
+----------
+
+For example:
+
+```
+LR
+
+11A = (11B, XXX)
+```
+
+So, in this example, you end up entirely on nodes that end in `Z` after `**6**` steps.
+";
+
+    #[test]
+    fn extract_from_a_page() {
+        let examples = extract_examples(PAGE);
+
+        assert_eq!(
+            examples,
+            [Example {
+                input: "Time:      7  15   30\nDistance:  9  40  200\n".to_string(),
+                answers: [Some("288".to_string()), Some("71503".to_string())],
+            }]
+        );
+        assert_eq!(html_text("<em>a</em> &lt;&amp;lt;&gt;"), "a <&lt;>");
+    }
+
+    #[test]
+    fn extract_from_markdown() {
+        let examples = extract_examples(MARKDOWN);
+
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].input, "RL\n\nAAA = (BBB, CCC)\n");
+        assert_eq!(examples[0].answers, [Some("2".to_string()), None]);
+        assert_eq!(examples[1].input, "LR\n\n11A = (11B, XXX)\n");
+        assert_eq!(examples[1].answers, [None, Some("6".to_string())]);
+
+        // plain code isn't an answer, only the emphasised one
+        let (_, answers) = markdown_code("`7` and *`8`* but not `*` or *9*");
+        assert_eq!(answers, ["8"]);
+    }
+
+    #[test]
+    fn declare_the_examples() {
+        let examples = [
+            Example {
+                input: "0 3 6\n".to_string(),
+                answers: [Some("114".to_string()), Some("-2".to_string())],
+            },
+            Example {
+                input: "x".to_string(),
+                answers: [None, Some("CMZ".to_string())],
+            },
+        ];
+
+        assert_eq!(
+            declare_examples(&examples),
+            r#"common::examples! {
+    example1: include_str!("../examples/1.txt") => {
+        compute_part1: 114,
+        compute_part2: -2,
+    }
+    example2: include_str!("../examples/2.txt") => {
+        compute_part2: "CMZ",
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn report_the_wrong_answer() {
+        check_example("same", "solve", "1 2\n", 3, 3);
+
+        let panic = std::panic::catch_unwind(|| check_example("sum", "solve", "1 2\n3\n", 5, 6));
+        let message = panic.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(
+            *message,
+            "solve gives the wrong answer on example `sum`\n  expected: 6\n     found: 5\n  on the input:\n    1 2\n    3\n"
+        );
+
+        let failed = crate::Result::<u64>::Err(crate::format_err!("no numbers"));
+        let panic = std::panic::catch_unwind(|| check_example("none", "solve", "x\n", failed, 6));
+        let message = panic.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(
+            *message,
+            "solve fails on example `none`: no numbers\n  expected: 6\n  on the input:\n    x\n"
+        );
+    }
+
+    fn sum(input: &str) -> u64 {
+        crate::parse_line_numbers(input).iter().sum()
+    }
+
+    fn count(input: &str) -> usize {
+        input.split_whitespace().count()
+    }
+
+    fn largest(input: &str) -> crate::Result<u64> {
+        crate::parse_line_numbers(input)
+            .into_iter()
+            .max()
+            .ok_or_else(|| crate::format_err!("no numbers"))
+    }
+
+    fn first_word(input: &str) -> String {
+        input
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    const NUMBERS: &str = "1 2 3";
+
+    crate::examples! {
+        numbers: NUMBERS => { sum: 6, count: 3, largest: 3, first_word: "1" }
+        literal: "10 20" => {
+            sum: 30,
+        }
+    }
+}
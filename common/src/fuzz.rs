@@ -0,0 +1,269 @@
+use std::{
+    borrow::Borrow,
+    env, fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{Answer, Rng};
+
+/// Recent inputs that didn't panic are mutated again, so mutations pile up over time.
+const POOL: usize = 64;
+
+/// Minimizing stops after this many attempts, it's only there to make the crash readable.
+const MAX_MINIMIZE: usize = 10_000;
+
+/// Bytes that tend to matter to parsers.
+const INTERESTING: &[u8] = b"\0\t\n\r -+:;,.|#{}()0159azAZ\x7f\x80\xc3\xff";
+
+/// Text that tends to break parsers when it shows up in the middle of an input.
+const TOKENS: &[&str] = &[
+    "0",
+    "-1",
+    "+",
+    "4294967296",
+    "18446744073709551616",
+    "99999999999999999999999999999999999999999",
+    "é",
+    "\u{2028}",
+    "\r\n",
+    "\n\n",
+    ": ",
+    "  ",
+];
+
+/// An input that made the target panic, shrunk as much as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crash {
+    pub input: Vec<u8>,
+    pub message: String,
+}
+
+impl fmt::Display for Crash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "panicked with `{}` on {:?}",
+            self.message,
+            String::from_utf8_lossy(&self.input)
+        )
+    }
+}
+
+impl std::error::Error for Crash {}
+
+/// Feeds `target` random mutations of the corpus, like a (much dumber) libFuzzer target, and
+/// reports the first input that makes it panic. Targets have the same `&[u8]` signature as the
+/// libFuzzer ones, so they work with `cargo fuzz` too.
+///
+/// `FUZZ_ITERATIONS` and `FUZZ_SEED` can be set to fuzz for longer or somewhere else than the
+/// tests do by default.
+pub fn fuzz(corpus: &[&str], iterations: usize, target: impl Fn(&[u8])) -> Result<(), Crash> {
+    let from_env = |name| env::var(name).ok().and_then(|value| value.parse().ok());
+    let iterations = from_env("FUZZ_ITERATIONS").unwrap_or(iterations as u64);
+    let mut rng = Rng::new(from_env("FUZZ_SEED").unwrap_or(0));
+
+    let corpus = corpus
+        .iter()
+        .map(|input| input.as_bytes().to_vec())
+        .collect::<Vec<_>>();
+    let run = |input: &[u8]| {
+        panic::catch_unwind(AssertUnwindSafe(|| target(input))).map_err(|payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default()
+        })
+    };
+
+    // the corpus goes first as it is, then it's only mutations
+    let mut pool: Vec<Vec<u8>> = Vec::new();
+    for iteration in 0..iterations as usize {
+        let input = match corpus.get(iteration) {
+            Some(input) => input.clone(),
+            None => {
+                let mut input = if !pool.is_empty() && rng.chance(0.5) {
+                    rng.choose(&pool).clone()
+                } else if !corpus.is_empty() {
+                    rng.choose(&corpus).clone()
+                } else {
+                    Vec::new()
+                };
+                for _ in 0..rng.range(1..5) {
+                    mutate(&mut rng, &mut input, &corpus);
+                }
+                input
+            }
+        };
+
+        match run(&input) {
+            Ok(()) if pool.len() < POOL => pool.push(input),
+            Ok(()) => {
+                let idx = rng.index(POOL);
+                pool[idx] = input;
+            }
+            Err(message) => return Err(minimize(input, message, run)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Same as [`fuzz`] for solutions that take the whole input as text, like the `compute_part1`
+/// and `compute_part2` of a day. Inputs that aren't UTF-8 are read lossily, and the errors the
+/// solutions return are formatted too.
+pub fn fuzz_text<I, A>(
+    corpus: &[&str],
+    iterations: usize,
+    solutions: &[fn(&I) -> A],
+) -> Result<(), Crash>
+where
+    I: ?Sized,
+    A: Answer,
+    String: Borrow<I>,
+{
+    fuzz(corpus, iterations, |data| {
+        let input = String::from_utf8_lossy(data).into_owned();
+        for solve in solutions {
+            let _ = solve(input.borrow()).answer();
+        }
+    })
+}
+
+/// Change the input a little, in one of the ways that usually find bugs.
+fn mutate(rng: &mut Rng, input: &mut Vec<u8>, corpus: &[Vec<u8>]) {
+    let at = |rng: &mut Rng, input: &Vec<u8>| rng.index(input.len() + 1);
+
+    match rng.below(8) {
+        0 if !input.is_empty() => {
+            let idx = rng.index(input.len());
+            input[idx] ^= 1 << rng.below(8);
+        }
+        1 if !input.is_empty() => {
+            let idx = rng.index(input.len());
+            input[idx] = *rng.choose(INTERESTING);
+        }
+        2 => {
+            let idx = at(rng, input);
+            input.insert(idx, *rng.choose(INTERESTING));
+        }
+        3 if !input.is_empty() => {
+            let start = rng.index(input.len());
+            let end = rng.range(start as u64..input.len() as u64 + 1) as usize;
+            input.drain(start..end.min(start + 8));
+        }
+        4 if !input.is_empty() => {
+            let start = rng.index(input.len());
+            let end = rng.range(start as u64..input.len() as u64 + 1) as usize;
+            let chunk = input[start..end].to_vec();
+            let idx = at(rng, input);
+            input.splice(idx..idx, chunk);
+        }
+        5 if !corpus.is_empty() => {
+            let other = rng.choose(corpus);
+            let idx = at(rng, input);
+            let from = rng.index(other.len() + 1);
+            input.truncate(idx);
+            input.extend_from_slice(&other[from..]);
+        }
+        6 => {
+            let idx = at(rng, input);
+            input.splice(idx..idx, rng.choose(TOKENS).bytes());
+        }
+        _ => {
+            let idx = at(rng, input);
+            input.truncate(idx);
+        }
+    }
+}
+
+/// Remove chunks of the input, from big to single bytes, for as long as it still panics.
+fn minimize(
+    mut input: Vec<u8>,
+    mut message: String,
+    run: impl Fn(&[u8]) -> Result<(), String>,
+) -> Crash {
+    let mut attempts = 0;
+    let mut chunk = input.len() / 2;
+    while chunk > 0 && attempts < MAX_MINIMIZE {
+        let mut start = 0;
+        while start < input.len() && attempts < MAX_MINIMIZE {
+            attempts += 1;
+            let mut smaller = input.clone();
+            smaller.drain(start..(start + chunk).min(input.len()));
+
+            match run(&smaller) {
+                Err(smaller_message) => {
+                    input = smaller;
+                    message = smaller_message;
+                }
+                Ok(()) => start += chunk,
+            }
+        }
+        chunk /= 2;
+    }
+
+    Crash { input, message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_and_shrink_a_panic() {
+        // it only breaks when there's a `#` after a digit
+        let target = |input: &[u8]| {
+            if let Some(pos) = input
+                .windows(2)
+                .position(|w| w[0].is_ascii_digit() && w[1] == b'#')
+            {
+                panic!("bad byte at {}", pos);
+            }
+        };
+
+        let crash = fuzz(&["a1 b2 c3", "x: 4"], 10_000, target).unwrap_err();
+        assert_eq!(crash.input.len(), 2);
+        assert_eq!(crash.input[1], b'#');
+        assert_eq!(crash.message, "bad byte at 0");
+
+        assert!(fuzz(&["a1 b2"], 1000, |input| {
+            let _ = std::str::from_utf8(input);
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn fuzz_text_solutions() {
+        fn first_number(input: &str) -> anyhow::Result<u32> {
+            Ok(input
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .parse()?)
+        }
+        fn last_number(input: &str) -> anyhow::Result<u32> {
+            Ok(input
+                .split_whitespace()
+                .last()
+                .unwrap_or_default()
+                .parse()?)
+        }
+        assert!(fuzz_text(&["1 2 3"], 1000, &[first_number, last_number]).is_ok());
+
+        // the errors are formatted, so one that panics while doing it is caught
+        struct Broken;
+        impl fmt::Display for Broken {
+            fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+                panic!("can't show this error")
+            }
+        }
+        // taking a `&String` like most of the days do
+        #[allow(clippy::ptr_arg)]
+        fn broken(input: &String) -> Result<u32, Broken> {
+            input.parse().map_err(|_| Broken)
+        }
+        let crash = fuzz_text(&["1"], 1000, &[broken]).unwrap_err();
+        assert_eq!(crash.message, "can't show this error");
+    }
+}
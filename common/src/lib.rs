@@ -1,30 +1,41 @@
-use regex::Regex;
-
+mod blocks;
+mod diagnostic;
+mod differential;
+mod examples;
+mod fuzz;
+mod lines;
+mod matcher;
+mod numbers;
 mod ok_iterator;
+mod pattern;
+mod random;
 
 pub mod prelude {
     pub use anyhow::{bail, ensure, format_err, Context, Error, Ok, Result};
 }
 
 pub use anyhow::{bail, ensure, format_err, Context, Error, Ok, Result};
-pub use ok_iterator::OkIterator;
-
+pub use blocks::{blocks, Block, Blocks, Sections};
+pub use diagnostic::{Diagnose, Diagnostic};
+pub use differential::{differential, shrink_number, shrink_vec, Disagreement};
+pub use examples::{check_example, declare_examples, extract_examples, Answer, Example};
+pub use fuzz::{fuzz, fuzz_text, Crash};
+pub use lines::{LineSource, ParsedLines};
+pub use matcher::{FindOverlapping, Match, Matcher};
+pub use numbers::{joined_number, numbers, numbers_array, Numbers, ScanInteger};
+pub use ok_iterator::{OkEnumerateContext, OkIterator};
+pub use pattern::{Captures, FromCaptures, ParseError, Pattern};
+pub use random::{generate_reproducible, generate_request, Rng};
+
+/// All the numbers of a line, the ones that don't fit in an `u64` are skipped. Prefer
+/// [`numbers`] when those should be reported.
 pub fn parse_line_numbers(line: &str) -> Vec<u64> {
-    let numbers_re = Regex::new(r"(\d+)").unwrap();
-
-    numbers_re
-        .find_iter(line)
-        .filter_map(|digits| digits.as_str().parse().ok())
-        .collect()
+    numbers(line).filter_map(Result::ok).collect()
 }
 
+/// Same as [`parse_line_numbers`] but a `-` before the digits makes them negative.
 pub fn parse_line_neg_numbers(line: &str) -> Vec<i64> {
-    let numbers_re = Regex::new(r"(-?\d+)").unwrap();
-
-    numbers_re
-        .find_iter(line)
-        .filter_map(|digits| digits.as_str().parse().ok())
-        .collect()
+    numbers(line).filter_map(Result::ok).collect()
 }
 
 #[cfg(test)]
@@ -0,0 +1,226 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    marker::PhantomData,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{Diagnose, ParseError};
+
+/// Reads an input one line at a time, reusing the same buffer, so inputs of any size can be
+/// processed in constant memory.
+///
+/// Lines come without their `\n` or `\r\n` ending. Errors say which line they come from, and
+/// which file when it was opened with [`LineSource::open`].
+#[derive(Debug)]
+pub struct LineSource<R> {
+    reader: R,
+    buffer: String,
+    lines_read: usize,
+    file: Option<String>,
+    skip_blank: bool,
+}
+
+impl LineSource<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("can't open {}", path.display()))?;
+
+        let mut source = Self::new(BufReader::new(file));
+        source.file = Some(path.display().to_string());
+        Ok(source)
+    }
+}
+
+impl<R: BufRead> LineSource<R> {
+    /// Read the lines from anything buffered, like `raw_data.as_bytes()` for a string.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: String::new(),
+            lines_read: 0,
+            file: None,
+            skip_blank: false,
+        }
+    }
+
+    /// Ignore the lines that are empty or only have whitespace.
+    pub fn skip_blank(mut self) -> Self {
+        self.skip_blank = true;
+        self
+    }
+
+    /// The next line, `None` once the input is over.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<&str>> {
+        self.read().map(|line| line.map(|(_, line)| line))
+    }
+
+    /// The next line with its 0-based number.
+    fn read(&mut self) -> Option<Result<(usize, &str)>> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.lines_read += 1,
+                Err(err) => return Some(Err(err).with_context(|| self.location())),
+            }
+
+            // same as `str::lines`, a `\r` only goes away when it's before the `\n`
+            let line = match self.buffer.strip_suffix('\n') {
+                Some(line) => line.strip_suffix('\r').unwrap_or(line),
+                None => &self.buffer,
+            };
+            if !(self.skip_blank && line.trim().is_empty()) {
+                let len = line.len();
+                return Some(Ok((self.lines_read - 1, &self.buffer[..len])));
+            }
+        }
+    }
+
+    /// 0-based number of the last line read.
+    pub fn line_index(&self) -> usize {
+        self.lines_read.saturating_sub(1)
+    }
+
+    /// Where the last line read is, like `input.txt:12` or `line 12`.
+    pub fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}:{}", file, self.lines_read),
+            None => format!("line {}", self.lines_read),
+        }
+    }
+
+    /// Fold all the lines, stopping at the first error.
+    pub fn fold_lines<B, F>(mut self, init: B, mut f: F) -> Result<B>
+    where
+        F: FnMut(B, &str) -> Result<B>,
+    {
+        let mut acc = init;
+        while let Some(line) = self.next() {
+            let line = line?;
+            acc = match f(acc, line) {
+                Ok(acc) => acc,
+                Err(err) => return Err(err.context(self.location())),
+            };
+        }
+
+        Ok(acc)
+    }
+
+    /// Parse every line into a `T`, errors are rendered as a [`crate::Diagnostic`].
+    pub fn parse<T>(self) -> ParsedLines<R, T>
+    where
+        T: FromStr<Err = ParseError>,
+    {
+        ParsedLines {
+            source: self,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Iterator returned by [`LineSource::parse`].
+#[derive(Debug)]
+pub struct ParsedLines<R, T> {
+    source: LineSource<R>,
+    _marker: PhantomData<T>,
+}
+
+impl<R, T> Iterator for ParsedLines<R, T>
+where
+    R: BufRead,
+    T: FromStr<Err = ParseError>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parsed = match self.source.read()? {
+            Ok((index, line)) => line.parse::<T>().on_line(index, line),
+            Err(err) => return Some(Err(err)),
+        };
+
+        Some(parsed.map_err(|diagnostic| match &self.source.file {
+            Some(file) => diagnostic.in_file(file.as_str()).into(),
+            None => diagnostic.into(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{Diagnostic, OkIterator, Pattern};
+
+    #[derive(Debug)]
+    struct Pair(u32, u32);
+
+    impl FromStr for Pair {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (a, b) = Pattern::new("{} {}")?.parse(s)?;
+            Ok(Pair(a, b))
+        }
+    }
+
+    #[test]
+    fn fold_over_lines() -> Result<()> {
+        let input = "1 2\r\n\n  \n3 4\n";
+
+        let lengths =
+            LineSource::new(input.as_bytes()).fold_lines(Vec::new(), |mut lengths, line| {
+                lengths.push(line.len());
+                Ok(lengths)
+            })?;
+        assert_eq!(lengths, [3, 0, 2, 3]);
+
+        let sum = LineSource::new(input.as_bytes())
+            .skip_blank()
+            .parse::<Pair>()
+            .ok_fold(0, |sum, Pair(a, b)| sum + a * b)?;
+        assert_eq!(sum, 14);
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_know_their_line() {
+        let input = "1 2\n\n3 x\n";
+
+        let err = LineSource::new(input.as_bytes())
+            .skip_blank()
+            .parse::<Pair>()
+            .ok_collect_vec()
+            .unwrap_err();
+        let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!((diagnostic.line, diagnostic.column()), (3, 3));
+
+        let err = LineSource::new(input.as_bytes())
+            .fold_lines((), |_, line| match line {
+                "3 x" => anyhow::bail!("bad line"),
+                _ => Ok(()),
+            })
+            .unwrap_err();
+        assert_eq!(format!("{:#}", err), "line 3: bad line");
+
+        assert!(LineSource::open("does/not/exist.txt").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn same_lines_as_str_lines(input in "[a \r\n]{0,20}") {
+            let lines = LineSource::new(input.as_bytes()).fold_lines(Vec::new(), |mut lines, line| {
+                lines.push(line.to_string());
+                Ok(lines)
+            });
+
+            prop_assert_eq!(lines.unwrap(), input.lines().collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use anyhow::{ensure, Result};
+
+/// A pattern found by a [`Matcher`], `start..end` is its byte range on the searched text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// index of the pattern, in the order they were given
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds many patterns at once in a single pass over the text, Aho–Corasick style.
+///
+/// The trie of the patterns is turned into a full automaton, so each byte of the text is a
+/// single table lookup no matter how many patterns there are.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    /// next state for every state and byte
+    delta: Vec<[u32; 256]>,
+    /// patterns ending on each state, the longest first
+    outputs: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+impl Matcher {
+    pub fn new<I, P>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut delta = vec![[0; 256]];
+        let mut outputs = vec![Vec::new()];
+        let mut lengths = Vec::new();
+
+        // the trie, where 0 means there is no edge since the root can't be a child
+        for (pattern, bytes) in patterns.into_iter().enumerate() {
+            let bytes = bytes.as_ref();
+            ensure!(!bytes.is_empty(), "pattern {} is empty", pattern);
+
+            let mut state = 0;
+            for &byte in bytes {
+                if delta[state][byte as usize] == 0 {
+                    delta.push([0; 256]);
+                    outputs.push(Vec::new());
+                    delta[state][byte as usize] = (delta.len() - 1) as u32;
+                }
+                state = delta[state][byte as usize] as usize;
+            }
+            outputs[state].push(pattern);
+            lengths.push(bytes.len());
+        }
+
+        // breadth first, so the failure of a state is always done before the state itself.
+        // Missing edges take the edge of the failure, which makes the trie a full automaton.
+        let mut failure = vec![0; delta.len()];
+        let mut queue = delta[0]
+            .iter()
+            .filter(|&&child| child != 0)
+            .map(|&child| child as usize)
+            .collect::<VecDeque<_>>();
+
+        while let Some(state) = queue.pop_front() {
+            let fallback = failure[state];
+            let inherited = outputs[fallback].clone();
+            outputs[state].extend(inherited);
+
+            for byte in 0..256 {
+                let child = delta[state][byte] as usize;
+                if child == 0 {
+                    delta[state][byte] = delta[fallback][byte];
+                } else {
+                    failure[child] = delta[fallback][byte] as usize;
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        Ok(Self {
+            delta,
+            outputs,
+            lengths,
+        })
+    }
+
+    /// Number of patterns.
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// All the matches, even the ones overlapping each other like `eight` and `two` on
+    /// `eightwo`. They come sorted by where they end, the longest first when they end on the
+    /// same place.
+    pub fn find_overlapping<'m, 'h>(&'m self, haystack: &'h str) -> FindOverlapping<'m, 'h> {
+        FindOverlapping {
+            matcher: self,
+            haystack: haystack.as_bytes(),
+            pos: 0,
+            state: 0,
+            output: 0,
+        }
+    }
+}
+
+/// Iterator returned by [`Matcher::find_overlapping`].
+#[derive(Debug, Clone)]
+pub struct FindOverlapping<'m, 'h> {
+    matcher: &'m Matcher,
+    haystack: &'h [u8],
+    /// bytes of the haystack already fed to the automaton
+    pos: usize,
+    state: usize,
+    /// outputs of the current state already reported
+    output: usize,
+}
+
+impl<'m, 'h> Iterator for FindOverlapping<'m, 'h> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&pattern) = self.matcher.outputs[self.state].get(self.output) {
+                self.output += 1;
+                return Some(Match {
+                    pattern,
+                    start: self.pos - self.matcher.lengths[pattern],
+                    end: self.pos,
+                });
+            }
+
+            let &byte = self.haystack.get(self.pos)?;
+            self.state = self.matcher.delta[self.state][byte as usize] as usize;
+            self.pos += 1;
+            self.output = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn report_overlapping_matches() -> Result<()> {
+        let matcher = Matcher::new(["one", "two", "eight", "ne", "o"])?;
+        let found = |text| {
+            matcher
+                .find_overlapping(text)
+                .map(|found| (found.pattern, found.start..found.end))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(found("eightwo"), [(2, 0..5), (1, 4..7), (4, 6..7)]);
+        assert_eq!(found("twone"), [(1, 0..3), (4, 2..3), (0, 2..5), (3, 3..5)]);
+        assert_eq!(found("xyz"), []);
+        assert_eq!(matcher.len(), 5);
+        assert!(Matcher::new(["a", ""]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn same_as_searching_each_pattern() -> Result<()> {
+        let patterns = ["aa", "a", "aab", "ba", "b"];
+        let matcher = Matcher::new(patterns)?;
+        let text = "aabaabbaaab";
+
+        let mut expected = patterns
+            .iter()
+            .enumerate()
+            .flat_map(|(pattern, needle)| {
+                (0..=text.len() - needle.len())
+                    .filter(move |&start| text[start..].starts_with(needle))
+                    .map(move |start| (start + needle.len(), needle.len(), pattern))
+            })
+            .collect::<Vec<_>>();
+        expected.sort_by_key(|&(end, len, _)| (end, Reverse(len)));
+
+        let found = matcher
+            .find_overlapping(text)
+            .map(|found| (found.end, found.end - found.start, found.pattern))
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn find_every_occurrence(
+            patterns in prop::collection::vec("[ab]{1,3}", 1..6),
+            text in "[abc]{0,30}",
+        ) {
+            let matcher = Matcher::new(&patterns).unwrap();
+            let mut found = matcher
+                .find_overlapping(&text)
+                .map(|found| (found.end, Reverse(found.end - found.start), found.pattern))
+                .collect::<Vec<_>>();
+
+            // sorted by end and the longest first, in any order for the same pattern twice
+            prop_assert!(found
+                .windows(2)
+                .all(|pair| (pair[0].0, pair[0].1) <= (pair[1].0, pair[1].1)));
+            found.sort();
+
+            let mut expected = patterns
+                .iter()
+                .enumerate()
+                .flat_map(|(pattern, needle)| {
+                    (0..text.len())
+                        .filter(|&start| text[start..].starts_with(needle.as_str()))
+                        .map(move |start| (start + needle.len(), Reverse(needle.len()), pattern))
+                })
+                .collect::<Vec<_>>();
+            expected.sort();
+            prop_assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::{any::type_name, marker::PhantomData};
+
+use crate::ParseError;
+
+/// Integers that can be read digit by digit by the number scanner.
+pub trait ScanInteger: Copy + Default {
+    const SIGNED: bool;
+
+    /// `self * 10 + digit`, or `self * 10 - digit` for negative numbers, `None` on overflow.
+    fn push_digit(self, digit: u8, negative: bool) -> Option<Self>;
+}
+
+macro_rules! scan_integer {
+    ($signed:literal => $($ty:ty),+) => {
+        $(
+            impl ScanInteger for $ty {
+                const SIGNED: bool = $signed;
+
+                #[inline]
+                fn push_digit(self, digit: u8, negative: bool) -> Option<Self> {
+                    let shifted = self.checked_mul(10)?;
+                    if negative {
+                        shifted.checked_sub(digit as Self)
+                    } else {
+                        shifted.checked_add(digit as Self)
+                    }
+                }
+            }
+        )+
+    };
+}
+
+scan_integer!(false => u8, u16, u32, u64, u128, usize);
+scan_integer!(true => i8, i16, i32, i64, i128, isize);
+
+/// Lazy iterator over the numbers of a string, see [`numbers`].
+#[derive(Debug, Clone)]
+pub struct Numbers<'a, T> {
+    input: &'a [u8],
+    pos: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: ScanInteger> Iterator for Numbers<'a, T> {
+    type Item = Result<T, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.input;
+        let start = self.pos + input[self.pos..].iter().position(u8::is_ascii_digit)?;
+
+        // a sign only counts when it's right before the digits
+        let (negative, span_start) = match start.checked_sub(1).map(|pos| input[pos]) {
+            Some(b'-') if T::SIGNED => (true, start - 1),
+            Some(b'+') => (false, start - 1),
+            _ => (false, start),
+        };
+
+        let mut value = Some(T::default());
+        let mut end = start;
+        while let Some(&digit @ b'0'..=b'9') = input.get(end) {
+            value = value.and_then(|value| value.push_digit(digit - b'0', negative));
+            end += 1;
+        }
+        self.pos = end;
+
+        Some(value.ok_or_else(|| {
+            ParseError::new(
+                span_start..end,
+                format!("a number that fits in `{}`", type_name::<T>()),
+            )
+        }))
+    }
+}
+
+/// Lazily scan all the numbers of a string, everything that isn't a digit is a separator.
+///
+/// For signed types a `-` right before the digits makes the number negative, and on any type a
+/// `+` right before the digits is accepted. Numbers that don't fit in `T` are reported as an
+/// error instead of being skipped, and the scanning carries on after them.
+pub fn numbers<T: ScanInteger>(input: &str) -> Numbers<'_, T> {
+    Numbers {
+        input: input.as_bytes(),
+        pos: 0,
+        _marker: PhantomData,
+    }
+}
+
+/// Scan exactly `N` numbers from a string.
+pub fn numbers_array<T: ScanInteger, const N: usize>(input: &str) -> Result<[T; N], ParseError> {
+    let mut result = [T::default(); N];
+    let mut iter = numbers::<T>(input);
+
+    for (idx, slot) in result.iter_mut().enumerate() {
+        *slot = iter.next().ok_or_else(|| {
+            ParseError::new(
+                input.len()..input.len(),
+                format!("{} numbers, found {}", N, idx),
+            )
+        })??;
+    }
+
+    let rest = iter.pos;
+    match iter.next() {
+        None => Ok(result),
+        Some(_) => Err(ParseError::new(
+            rest..input.len(),
+            format!("only {} numbers", N),
+        )),
+    }
+}
+
+/// Read all the digits of a string as a single number, ignoring the spaces between them, so
+/// `"Time:      7  15   30"` gives `71530`.
+///
+/// Anything before the first digit is skipped, after it only digits and whitespace are allowed.
+pub fn joined_number<T: ScanInteger>(input: &str) -> Result<T, ParseError> {
+    let bytes = input.as_bytes();
+    let start = bytes
+        .iter()
+        .position(u8::is_ascii_digit)
+        .ok_or_else(|| ParseError::new(0..input.len(), "a number"))?;
+    let negative = T::SIGNED && start > 0 && bytes[start - 1] == b'-';
+
+    let mut value = T::default();
+    for (pos, &byte) in bytes.iter().enumerate().skip(start) {
+        match byte {
+            b'0'..=b'9' => {
+                value = value.push_digit(byte - b'0', negative).ok_or_else(|| {
+                    ParseError::new(
+                        start..input.len(),
+                        format!("a number that fits in `{}`", type_name::<T>()),
+                    )
+                })?;
+            }
+            _ if byte.is_ascii_whitespace() => {}
+            _ => return Err(ParseError::new(pos..pos + 1, "a digit or a space")),
+        }
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::OkIterator;
+
+    /// Text between numbers, it doesn't end with a sign so it can't change them.
+    fn separator() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(vec![" ", "  ", ", ", ": ", " | ", "x", "->", "\t"])
+    }
+
+    #[test]
+    fn scan_numbers_of_any_type() {
+        let line = "seeds: 79 14 -55 +13";
+
+        assert_eq!(
+            numbers::<u8>(line).ok_collect_vec().unwrap(),
+            [79, 14, 55, 13]
+        );
+        assert_eq!(
+            numbers::<i64>(line).ok_collect_vec().unwrap(),
+            [79, 14, -55, 13]
+        );
+        assert_eq!(numbers::<i32>("1-4 m").ok_collect_vec().unwrap(), [1, -4]);
+    }
+
+    #[test]
+    fn report_overflows() {
+        let mut iter = numbers::<u8>("12 300 4");
+
+        assert_eq!(iter.next(), Some(Ok(12)));
+        assert_eq!(iter.next().unwrap().unwrap_err().span, 3..6);
+        assert_eq!(iter.next(), Some(Ok(4)));
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(numbers::<i8>("-128").next(), Some(Ok(-128)));
+        assert!(numbers::<i8>("128").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn fixed_amount_of_numbers() {
+        assert_eq!(numbers_array::<u64, 3>("50 98 2"), Ok([50, 98, 2]));
+
+        let err = numbers_array::<u64, 3>("50 98").unwrap_err();
+        assert_eq!(err.expected, "3 numbers, found 2");
+
+        let err = numbers_array::<u64, 2>("50 98 2").unwrap_err();
+        assert_eq!((err.span, err.expected.as_str()), (5..7, "only 2 numbers"));
+    }
+
+    #[test]
+    fn numbers_split_by_spaces() {
+        assert_eq!(joined_number::<u64>("Time:      7  15   30"), Ok(71530));
+        assert_eq!(joined_number::<i32>("x: -1 2"), Ok(-12));
+        assert_eq!(joined_number::<u64>("7 15 x").unwrap_err().span, 5..6);
+        assert!(joined_number::<u64>("Time:").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn signed_round_trip(
+            values in prop::collection::vec((any::<i64>(), any::<bool>(), separator()), 0..10),
+        ) {
+            let line = values
+                .iter()
+                .map(|&(value, plus, separator)| match value >= 0 && plus {
+                    true => format!("{}+{}", separator, value),
+                    false => format!("{}{}", separator, value),
+                })
+                .collect::<String>();
+
+            let expected = values.iter().map(|&(value, ..)| value).collect::<Vec<_>>();
+            prop_assert_eq!(numbers::<i64>(&line).ok_collect_vec().unwrap(), expected);
+        }
+
+        #[test]
+        fn minus_only_counts_on_signed_types(value in any::<u64>(), separator in separator()) {
+            let line = format!("{}-{}", separator, value);
+            prop_assert_eq!(numbers::<u64>(&line).ok_collect_vec().unwrap(), [value]);
+
+            let signed = numbers::<i64>(&line).next().unwrap();
+            match i64::try_from(-(value as i128)) {
+                Ok(negative) => prop_assert_eq!(signed, Ok(negative)),
+                Err(_) => prop_assert!(signed.is_err()),
+            }
+        }
+
+        #[test]
+        fn overflows_are_reported_and_skipped(
+            too_big in u64::MAX as u128 + 1..=u128::MAX,
+            next in any::<u64>(),
+        ) {
+            let line = format!("x {} {}", too_big, next);
+            let digits = too_big.to_string().len();
+
+            let mut iter = numbers::<u64>(&line);
+            prop_assert_eq!(iter.next().unwrap().unwrap_err().span, 2..2 + digits);
+            prop_assert_eq!(iter.next(), Some(Ok(next)));
+            prop_assert_eq!(iter.next(), None);
+        }
+
+        #[test]
+        fn arrays_need_the_exact_amount(values in prop::collection::vec(any::<u32>(), 0..6)) {
+            let line = values.iter().map(u32::to_string).collect::<Vec<_>>().join(" ");
+
+            match numbers_array::<u32, 3>(&line) {
+                Ok(array) => prop_assert_eq!(&array[..], &values[..]),
+                Err(err) if values.len() < 3 => {
+                    prop_assert_eq!(err.expected, format!("3 numbers, found {}", values.len()))
+                }
+                Err(err) => prop_assert_eq!(err.expected, "only 3 numbers"),
+            }
+        }
+
+        #[test]
+        fn joined_digits_are_one_number(value in any::<u64>(), spaces in prop::collection::vec(0_usize..3, 20)) {
+            let line = value
+                .to_string()
+                .chars()
+                .zip(spaces)
+                .flat_map(|(digit, spaces)| std::iter::repeat(' ').take(spaces).chain([digit]))
+                .collect::<String>();
+
+            prop_assert_eq!(joined_number::<u64>(&format!("Time:{}", line)), Ok(value));
+        }
+    }
+}
@@ -1,4 +1,11 @@
-use anyhow::{format_err, Error, Result};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+    iter::{Product, Sum},
+};
+
+use anyhow::{format_err, Context, Error, Result};
 
 pub trait OkIterator<T, E>
 where
@@ -27,6 +34,120 @@ where
             .try_into()
             .map_err(|err: Vec<_>| format_err!("Not {} long but {}", N, err.len()))
     }
+
+    #[inline]
+    fn ok_collect_hashset(&mut self) -> Result<HashSet<T>>
+    where
+        T: Eq + Hash,
+    {
+        self.ok_collect()
+    }
+
+    #[inline]
+    fn ok_collect_hashmap<K, V>(&mut self) -> Result<HashMap<K, V>>
+    where
+        K: Eq + Hash,
+        HashMap<K, V>: FromIterator<T>,
+    {
+        self.ok_collect()
+    }
+
+    #[inline]
+    fn ok_collect_btreemap<K, V>(&mut self) -> Result<BTreeMap<K, V>>
+    where
+        K: Ord,
+        BTreeMap<K, V>: FromIterator<T>,
+    {
+        self.ok_collect()
+    }
+
+    /// Fallible versions of the usual consumers, they all stop at the first error.
+    #[inline]
+    fn ok_fold<B, F>(&mut self, init: B, mut f: F) -> Result<B>
+    where
+        F: FnMut(B, T) -> B,
+    {
+        self.try_fold(init, |acc, item| item.map(|item| f(acc, item)))
+            .map_err(Into::into)
+    }
+
+    #[inline]
+    fn ok_sum<S>(&mut self) -> Result<S>
+    where
+        S: Sum<T>,
+    {
+        self.sum::<Result<_, _>>().map_err(Into::into)
+    }
+
+    #[inline]
+    fn ok_product<P>(&mut self) -> Result<P>
+    where
+        P: Product<T>,
+    {
+        self.product::<Result<_, _>>().map_err(Into::into)
+    }
+
+    #[inline]
+    fn ok_min_by_key<K, F>(&mut self, mut f: F) -> Result<Option<T>>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.ok_fold(None, |min: Option<(K, T)>, item| {
+            let key = f(&item);
+            match min {
+                Some((min_key, min)) if min_key <= key => Some((min_key, min)),
+                _ => Some((key, item)),
+            }
+        })
+        .map(|min| min.map(|(_, item)| item))
+    }
+
+    #[inline]
+    fn ok_max_by_key<K, F>(&mut self, mut f: F) -> Result<Option<T>>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.ok_fold(None, |max: Option<(K, T)>, item| {
+            let key = f(&item);
+            match max {
+                Some((max_key, max)) if max_key > key => Some((max_key, max)),
+                _ => Some((key, item)),
+            }
+        })
+        .map(|max| max.map(|(_, item)| item))
+    }
+
+    /// Stops at the first item that maps to something, errors after it are never seen.
+    #[inline]
+    fn ok_find_map<B, F>(&mut self, mut f: F) -> Result<Option<B>>
+    where
+        F: FnMut(T) -> Option<B>,
+    {
+        for item in self {
+            if let Some(found) = f(item.map_err(Into::into)?) {
+                return Ok(Some(found));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Add some context to the errors with the position of the item that failed, like
+    /// `.ok_enumerate_context(|idx| format!("line {}", idx + 1))`.
+    #[inline]
+    fn ok_enumerate_context<C, F>(self, context: F) -> OkEnumerateContext<Self, F>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnMut(usize) -> C,
+    {
+        OkEnumerateContext {
+            iter: self,
+            index: 0,
+            context,
+        }
+    }
 }
 
 impl<T, E, It> OkIterator<T, E> for It
@@ -35,3 +156,152 @@ where
     E: Into<Error>,
 {
 }
+
+/// Iterator returned by [`OkIterator::ok_enumerate_context`].
+pub struct OkEnumerateContext<I, F> {
+    iter: I,
+    index: usize,
+    context: F,
+}
+
+impl<T, E, C, I, F> Iterator for OkEnumerateContext<I, F>
+where
+    I: Iterator<Item = Result<T, E>>,
+    E: Into<Error>,
+    C: Display + Send + Sync + 'static,
+    F: FnMut(usize) -> C,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        let index = self.index;
+        self.index += 1;
+
+        Some(
+            item.map_err(Into::into)
+                .with_context(|| (self.context)(index)),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn parse(input: &str) -> impl Iterator<Item = Result<u32, std::num::ParseIntError>> + '_ {
+        input.split(' ').map(str::parse)
+    }
+
+    #[test]
+    fn fallible_consumers() -> Result<()> {
+        assert_eq!(parse("1 2 3 4").ok_sum::<u32>()?, 10);
+        assert_eq!(parse("1 2 3 4").ok_product::<u32>()?, 24);
+        assert_eq!(parse("1 2 3 4").ok_fold(0, |acc, v| acc * 10 + v)?, 1234);
+        assert_eq!(parse("3 12 5").ok_min_by_key(|v| v % 10)?, Some(12));
+        assert_eq!(parse("3 12 5").ok_max_by_key(|v| v % 10)?, Some(5));
+        assert_eq!(parse("").ok_max_by_key(|&v| v).ok(), None);
+        assert_eq!(
+            parse("1 2 x").ok_find_map(|v| (v > 1).then_some(v))?,
+            Some(2)
+        );
+        assert!(parse("1 x 3").ok_sum::<u32>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_into_maps_and_sets() -> Result<()> {
+        let pairs = || parse("1 2 2 3").map(|v| v.map(|v| (v, v * 2)));
+
+        assert_eq!(parse("1 2 2 3").ok_collect_hashset()?.len(), 3);
+        assert_eq!(pairs().ok_collect_hashmap()?[&3], 6);
+        assert_eq!(
+            pairs()
+                .ok_collect_btreemap()?
+                .into_keys()
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_know_where_they_came_from() {
+        let err = parse("1 2 x")
+            .ok_enumerate_context(|idx| format!("line {}", idx + 1))
+            .ok_sum::<u32>()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "line 3");
+
+        let err = ["ok", "fail"]
+            .into_iter()
+            .map(|item| match item {
+                "fail" => bail!("it failed"),
+                item => Ok(item),
+            })
+            .ok_enumerate_context(|idx| format!("item #{}", idx))
+            .ok_collect_vec()
+            .unwrap_err();
+        assert_eq!(format!("{:#}", err), "item #1: it failed");
+    }
+
+    proptest! {
+        #[test]
+        fn arrays_need_the_exact_length(values in prop::collection::vec(any::<u8>(), 0..8)) {
+            let array = values.iter().map(Ok::<_, Error>).ok_collect_array::<4>();
+
+            match array {
+                Ok(array) => prop_assert_eq!(array.map(|v| *v).to_vec(), values),
+                Err(err) => {
+                    prop_assert_ne!(values.len(), 4);
+                    prop_assert_eq!(err.to_string(), format!("Not 4 long but {}", values.len()));
+                }
+            }
+        }
+
+        #[test]
+        fn stop_at_the_first_error(items in prop::collection::vec(prop::option::of(any::<u8>()), 0..10)) {
+            let results = || {
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| item.ok_or_else(|| format_err!("item {} is missing", idx)))
+            };
+
+            let first_missing = items.iter().position(Option::is_none);
+            match results().ok_collect_vec() {
+                Ok(collected) => {
+                    prop_assert_eq!(first_missing, None);
+                    prop_assert_eq!(collected.len(), items.len());
+                }
+                Err(err) => {
+                    let idx = first_missing.unwrap();
+                    prop_assert_eq!(err.to_string(), format!("item {} is missing", idx));
+                    let err = results()
+                        .ok_enumerate_context(|idx| format!("at {}", idx))
+                        .ok_collect_vec()
+                        .unwrap_err();
+                    prop_assert_eq!(err.to_string(), format!("at {}", idx));
+                }
+            }
+        }
+
+        #[test]
+        fn min_and_max_like_the_std_ones(values in prop::collection::vec(any::<u8>(), 0..10)) {
+            let results = || values.iter().map(Ok::<_, Error>);
+            let key = |v: &&u8| **v % 7;
+
+            prop_assert_eq!(results().ok_min_by_key(key).unwrap(), values.iter().min_by_key(key));
+            prop_assert_eq!(results().ok_max_by_key(key).unwrap(), values.iter().max_by_key(key));
+        }
+    }
+}
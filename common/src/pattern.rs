@@ -0,0 +1,415 @@
+use std::{any::type_name, fmt, ops::Range, str::FromStr};
+
+/// Error produced when an input doesn't follow a [`Pattern`], or one of its fields can't be
+/// converted into the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number, when the caller knows it
+    pub line: Option<usize>,
+    /// byte range of the offending part of the input
+    pub span: Range<usize>,
+    /// what we were expecting to find there
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(span: Range<usize>, expected: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            span,
+            expected: expected.into(),
+        }
+    }
+
+    /// Tag the error with the (0-based) index of the line it came from.
+    pub fn at_line(mut self, index: usize) -> Self {
+        self.line = Some(index + 1);
+        self
+    }
+
+    /// Move the span by a number of bytes, for when only a slice of the line was parsed.
+    pub fn offset(mut self, by: usize) -> Self {
+        self.span = self.span.start + by..self.span.end + by;
+        self
+    }
+
+    /// 1-based column where the error starts
+    pub fn column(&self) -> usize {
+        self.span.start + 1
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}, column {}: ", line, self.column())?,
+            None => write!(f, "column {}: ", self.column())?,
+        }
+        write!(f, "expected {}", self.expected)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Option<String>),
+}
+
+/// A declarative line pattern, like `"#{id} @ {x},{y}: {w}x{h}"`.
+///
+/// Every `{}` or `{name}` is a non-empty field that extends until the next literal text is
+/// found, the last field takes the rest of the line. Spaces on the literal text match any
+/// amount of whitespace, so `"Card {id}:"` also accepts `"Card   1:"`. Use `{{` and `}}` to
+/// match braces.
+///
+/// Building a pattern is cheap, but when possible build it once outside the loop over the lines,
+/// or use [`pattern!`](crate::pattern) to keep it around from the first time it's needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    pub fn new(template: &str) -> Result<Self, ParseError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((pos, ch)) = chars.next() {
+            match ch {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => name.push(c),
+                            None => return Err(ParseError::new(pos..template.len(), "`}`")),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    } else if matches!(segments.last(), Some(Segment::Field(_))) {
+                        return Err(ParseError::new(pos..pos + 1, "literal text between fields"));
+                    }
+
+                    let name = name.trim();
+                    segments.push(Segment::Field((!name.is_empty()).then(|| name.to_string())));
+                }
+                '}' => return Err(ParseError::new(pos..pos + 1, "`}}` to match a brace")),
+                _ => literal.push(ch),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Match the input against the pattern, returning the raw text of each field.
+    pub fn captures<'a>(&self, input: &'a str) -> Result<Captures<'_, 'a>, ParseError> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+
+        for (idx, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Literal(literal) => {
+                    pos = match_literal(input, pos, literal).ok_or_else(|| {
+                        ParseError::new(pos..input.len(), format!("{:?}", literal))
+                    })?;
+                }
+                Segment::Field(name) => {
+                    let end = match self.segments.get(idx + 1) {
+                        Some(Segment::Literal(next)) => input[pos..]
+                            .char_indices()
+                            .skip(1)
+                            .map(|(offset, _)| pos + offset)
+                            .find(|&end| match_literal(input, end, next).is_some()),
+                        _ => (pos < input.len()).then_some(input.len()),
+                    };
+                    let end = end.ok_or_else(|| {
+                        ParseError::new(pos..input.len(), format!("field {}", field_name(name)))
+                    })?;
+
+                    fields.push((name.as_deref(), pos..end));
+                    pos = end;
+                }
+            }
+        }
+
+        if pos < input.len() {
+            return Err(ParseError::new(pos..input.len(), "end of line"));
+        }
+
+        Ok(Captures { input, fields })
+    }
+
+    /// Match the input and convert its fields into a tuple or any other [`FromCaptures`] type.
+    pub fn parse<T: FromCaptures>(&self, input: &str) -> Result<T, ParseError> {
+        T::from_captures(&self.captures(input)?)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Match a literal at the given position, returning the position right after it.
+fn match_literal(input: &str, mut pos: usize, literal: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut literal = literal.as_bytes();
+
+    while let Some((&expected, rest)) = literal.split_first() {
+        if expected == b' ' {
+            let start = pos;
+            while bytes.get(pos).map_or(false, u8::is_ascii_whitespace) {
+                pos += 1;
+            }
+            if pos == start {
+                return None;
+            }
+
+            // consecutive spaces on the pattern are already covered by the run above
+            literal = rest;
+            while let Some((b' ', rest)) = literal.split_first() {
+                literal = rest;
+            }
+        } else if bytes.get(pos) == Some(&expected) {
+            pos += 1;
+            literal = rest;
+        } else {
+            return None;
+        }
+    }
+
+    Some(pos)
+}
+
+fn field_name(name: &Option<String>) -> String {
+    name.as_ref()
+        .map_or_else(|| "value".to_string(), |name| format!("`{}`", name))
+}
+
+/// Raw text of the fields matched by a [`Pattern`].
+#[derive(Debug)]
+pub struct Captures<'p, 'a> {
+    input: &'a str,
+    fields: Vec<(Option<&'p str>, Range<usize>)>,
+}
+
+impl<'p, 'a> Captures<'p, 'a> {
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Text of the n-th field
+    pub fn str(&self, index: usize) -> Option<&'a str> {
+        self.fields
+            .get(index)
+            .map(|(_, span)| &self.input[span.clone()])
+    }
+
+    /// Text of the field with the given name
+    pub fn named_str(&self, name: &str) -> Option<&'a str> {
+        self.position(name).and_then(|index| self.str(index))
+    }
+
+    /// Byte range of the n-th field on the input
+    pub fn span(&self, index: usize) -> Option<Range<usize>> {
+        self.fields.get(index).map(|(_, span)| span.clone())
+    }
+
+    /// Byte range of the field with the given name
+    pub fn named_span(&self, name: &str) -> Option<Range<usize>> {
+        self.position(name).and_then(|index| self.span(index))
+    }
+
+    /// Convert the n-th field to the requested type
+    pub fn get<T: FromStr>(&self, index: usize) -> Result<T, ParseError> {
+        let (name, span) = self.fields.get(index).ok_or_else(|| {
+            ParseError::new(
+                self.input.len()..self.input.len(),
+                format!("field #{}", index),
+            )
+        })?;
+
+        self.input[span.clone()].trim().parse().map_err(|_| {
+            ParseError::new(
+                span.clone(),
+                format!(
+                    "{} to be a valid `{}`",
+                    field_name(&name.map(String::from)),
+                    type_name::<T>()
+                ),
+            )
+        })
+    }
+
+    /// Convert the field with the given name to the requested type
+    pub fn name<T: FromStr>(&self, name: &str) -> Result<T, ParseError> {
+        let index = self.position(name).ok_or_else(|| {
+            ParseError::new(
+                self.input.len()..self.input.len(),
+                format!("field `{}`", name),
+            )
+        })?;
+
+        self.get(index)
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|(n, _)| *n == Some(name))
+    }
+}
+
+/// Types that can be built from the fields of a [`Pattern`].
+pub trait FromCaptures: Sized {
+    fn from_captures(captures: &Captures) -> Result<Self, ParseError>;
+}
+
+macro_rules! tuple_from_captures {
+    ($len:literal => $($ty:ident $idx:tt),+) => {
+        impl<$($ty: FromStr),+> FromCaptures for ($($ty,)+) {
+            fn from_captures(captures: &Captures) -> Result<Self, ParseError> {
+                if captures.len() != $len {
+                    return Err(ParseError::new(
+                        0..captures.input.len(),
+                        format!("{} fields but the pattern has {}", $len, captures.len()),
+                    ));
+                }
+
+                Ok(($(captures.get::<$ty>($idx)?,)+))
+            }
+        }
+    };
+}
+
+tuple_from_captures!(1 => A 0);
+tuple_from_captures!(2 => A 0, B 1);
+tuple_from_captures!(3 => A 0, B 1, C 2);
+tuple_from_captures!(4 => A 0, B 1, C 2, D 3);
+tuple_from_captures!(5 => A 0, B 1, C 2, D 3, E 4);
+tuple_from_captures!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_from_captures!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_from_captures!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// A [`Pattern`] for a template that is known when writing the code, built the first time it's
+/// used and kept for all the calls after that, so it can go in a `FromStr` that parses a line.
+///
+/// ```ignore
+/// let captures = common::pattern!("Card {id}: {winners} | {numbers}").captures(line)?;
+/// ```
+#[macro_export]
+macro_rules! pattern {
+    ($template:literal) => {{
+        static PATTERN: std::sync::OnceLock<$crate::Pattern> = std::sync::OnceLock::new();
+        PATTERN.get_or_init(|| $crate::Pattern::new($template).expect("invalid pattern template"))
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn parse_claim_into_tuple() {
+        let pattern = Pattern::new("#{id} @ {x},{y}: {w}x{h}").unwrap();
+        let claim = pattern.parse::<(u32, u32, u32, u32, u32)>("#123 @ 3,2: 5x4");
+
+        assert_eq!(claim, Ok((123, 3, 2, 5, 4)));
+    }
+
+    #[test]
+    fn build_a_pattern_once() {
+        let card = || crate::pattern!("Card {id}: {winners} | {numbers}");
+
+        assert!(std::ptr::eq(card(), card()));
+        assert_eq!(
+            card(),
+            &Pattern::new("Card {id}: {winners} | {numbers}").unwrap()
+        );
+    }
+
+    #[test]
+    fn named_fields_and_flexible_spaces() {
+        let pattern = Pattern::new("Card {id}: {winners} | {numbers}").unwrap();
+        let captures = pattern.captures("Card   3:  1 21 53 | 69 82").unwrap();
+
+        assert_eq!(captures.name::<u32>("id"), Ok(3));
+        assert_eq!(captures.named_str("winners"), Some("1 21 53"));
+        assert_eq!(captures.named_str("numbers"), Some("69 82"));
+        assert_eq!(captures.named_span("winners"), Some(11..18));
+    }
+
+    #[test]
+    fn report_column_on_failure() {
+        let pattern = Pattern::new("{min}-{max} {letter}: {password}").unwrap();
+
+        let err = pattern
+            .parse::<(usize, usize, char, String)>("1-x m: abc")
+            .unwrap_err();
+        assert_eq!((err.column(), err.span.clone()), (3, 2..3));
+
+        let err = pattern.captures("1-4 m abc").unwrap_err().at_line(4);
+        assert_eq!(err.to_string(), "line 5, column 5: expected field `letter`");
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(Pattern::new("{a}{b}").is_err());
+        assert!(Pattern::new("{a").is_err());
+        assert!(Pattern::new("a}").is_err());
+        assert_eq!(
+            Pattern::new("{{{}}}").unwrap().parse::<(u8,)>("{7}"),
+            Ok((7,))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn parse_what_was_formatted(
+            min in any::<usize>(),
+            max in any::<usize>(),
+            letter in "[a-z]",
+            password in "[a-z]{1,12}",
+            spaces in 1_usize..4,
+        ) {
+            let pattern = Pattern::new("{min}-{max} {letter}: {password}").unwrap();
+            let line = format!("{}-{}{}{}: {}", min, max, " ".repeat(spaces), letter, password);
+
+            let parsed = pattern.parse::<(usize, usize, char, String)>(&line);
+            prop_assert_eq!(parsed, Ok((min, max, letter.chars().next().unwrap(), password.clone())));
+
+            let captures = pattern.captures(&line).unwrap();
+            for name in ["min", "max", "letter", "password"] {
+                let span = captures.named_span(name).unwrap();
+                prop_assert_eq!(captures.named_str(name), Some(&line[span]));
+            }
+        }
+
+        #[test]
+        fn errors_point_inside_the_line(line in "[0-9a-z :-]{0,20}") {
+            let pattern = Pattern::new("{min}-{max} {letter}: {password}").unwrap();
+
+            if let Err(err) = pattern.parse::<(usize, usize, char, String)>(&line) {
+                prop_assert!(err.span.start <= err.span.end && err.span.end <= line.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::{fmt::Debug, ops::Range};
+
+use anyhow::{bail, Context, Result};
+
+/// Small pseudo random generator (SplitMix64) for the input generators. The same seed gives the
+/// same numbers everywhere, so a generated input can always be made again from its seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform on `0..bound`, which can't be empty.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "nothing below 0 to pick from");
+
+        // multiply and keep the high half, throwing away the few values that would be biased
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let wide = self.next_u64() as u128 * bound as u128;
+            if wide as u64 >= threshold {
+                return (wide >> 64) as u64;
+            }
+        }
+    }
+
+    /// Uniform on the range, which can't be empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "empty range {:?}", range);
+        range.start + self.below(range.end - range.start)
+    }
+
+    /// A random index of something with that length.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /// `true` with that probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.index(idx + 1));
+        }
+    }
+}
+
+/// Generates an input from `seed` twice and fails unless both are the same, which is what the
+/// tests of a generator check first before solving the input it gives.
+pub fn generate_reproducible<I: PartialEq + Debug>(
+    generate: impl Fn(&mut Rng, usize) -> I,
+    seed: u64,
+    size: usize,
+) -> Result<I> {
+    let input = generate(&mut Rng::new(seed), size);
+    let again = generate(&mut Rng::new(seed), size);
+    if input != again {
+        bail!(
+            "seed {} gave two different inputs:\n{:?}\n{:?}",
+            seed,
+            input,
+            again
+        );
+    }
+    Ok(input)
+}
+
+/// Looks for `--generate <seed> <size>` on the arguments, which asks for a random input to be
+/// printed instead of solving the puzzle.
+pub fn generate_request(args: impl IntoIterator<Item = String>) -> Result<Option<(Rng, usize)>> {
+    let mut args = args.into_iter();
+    if args.next().as_deref() != Some("--generate") {
+        return Ok(None);
+    }
+
+    let (Some(seed), Some(size), None) = (args.next(), args.next(), args.next()) else {
+        bail!("usage: --generate <seed> <size>");
+    };
+    let seed = seed
+        .parse()
+        .with_context(|| format!("invalid seed `{}`", seed))?;
+    let size = size
+        .parse()
+        .with_context(|| format!("invalid size `{}`", size))?;
+
+    Ok(Some((Rng::new(seed), size)))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::{any, prop_assert, prop_assert_eq, proptest};
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let numbers = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..100).map(|_| rng.range(10..20)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+        assert!(numbers(7).iter().all(|n| (10..20).contains(n)));
+        for value in 10..20 {
+            assert!(numbers(7).contains(&value));
+        }
+
+        let mut rng = Rng::new(1);
+        let mut items = (0..50).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+        assert_eq!(rng.below(1), 0);
+        assert!((0..100).all(|_| !rng.chance(0.0) && rng.chance(1.0)));
+    }
+
+    #[test]
+    fn read_generate_requests() -> Result<()> {
+        let args = |line: &str| {
+            line.split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        let (mut rng, size) = generate_request(args("--generate 42 1000"))?.unwrap();
+        assert_eq!(size, 1000);
+        assert_eq!(rng.next_u64(), Rng::new(42).next_u64());
+
+        assert!(generate_request(args("report"))?.is_none());
+        assert!(generate_request(args(""))?.is_none());
+        assert!(generate_request(args("--generate 42")).is_err());
+        assert!(generate_request(args("--generate x 10")).is_err());
+        assert!(generate_request(args("--generate 1 2 3")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn check_generators_are_reproducible() -> Result<()> {
+        let digits = |rng: &mut Rng, size| (0..size).map(|_| rng.below(10)).collect::<Vec<_>>();
+        assert_eq!(generate_reproducible(digits, 3, 5)?.len(), 5);
+
+        let counter = std::cell::Cell::new(0);
+        let drifting = |_: &mut Rng, _| {
+            counter.set(counter.get() + 1);
+            counter.get()
+        };
+        assert!(generate_reproducible(drifting, 3, 5).is_err());
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn stay_in_bounds(seed in any::<u64>(), bound in 1_u64.., len in 0_usize..20) {
+            let mut rng = Rng::new(seed);
+            prop_assert!(rng.below(bound) < bound);
+            prop_assert!(rng.index(len + 1) <= len);
+
+            let mut items = (0..len).collect::<Vec<_>>();
+            rng.shuffle(&mut items);
+            items.sort();
+            prop_assert_eq!(items, (0..len).collect::<Vec<_>>());
+        }
+    }
+}